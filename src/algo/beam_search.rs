@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use crate::graph::{Graph, GraphWrapper, PointCoord, SearchEvent};
+
+use super::{distance_to_end, FrontierNode, PathFinder};
+
+// Expands the whole beam every step, only `beam_width` of the discovered points
+// closest to `end` are kept for the next one
+pub struct BeamSearch {
+    graph_wrapper: GraphWrapper,
+
+    beam_width: usize,
+    beam: Vec<(u32, PointCoord)>,
+}
+
+impl BeamSearch {
+    pub const BEAM_WIDTH: usize = 3;
+
+    fn initial_beam(graph_wrapper: &GraphWrapper) -> Vec<(u32, PointCoord)> {
+        graph_wrapper
+            .start_coords()
+            .iter()
+            .map(|start_coord| (0, start_coord.clone()))
+            .collect()
+    }
+}
+
+impl PathFinder for BeamSearch {
+    fn new(graph: Arc<Graph>) -> Box<dyn PathFinder>
+    where
+        Self: Sized,
+    {
+        let graph_wrapper = GraphWrapper::new(graph);
+        let beam = Self::initial_beam(&graph_wrapper);
+
+        println!(
+            "[I] BeamSearch[Heuristic: {}, beam width: {}]",
+            graph_wrapper.graph().distance_name(),
+            Self::BEAM_WIDTH
+        );
+
+        Box::new(Self {
+            graph_wrapper,
+            beam_width: Self::BEAM_WIDTH,
+            beam,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "BeamSearch"
+    }
+
+    fn step(&mut self) {
+        // Every point of the beam got pruned, `end` can't be reached anymore
+        if self.beam.is_empty() {
+            self.graph_wrapper.completed = true;
+            return;
+        }
+
+        let mut reached_points = vec![];
+
+        for (length, cur) in std::mem::take(&mut self.beam) {
+            self.graph_wrapper.emit(SearchEvent::Popped {
+                node: cur.clone(),
+                priority: distance_to_end(&self.graph_wrapper, &cur),
+            });
+
+            // Mark current as seen, not mark start
+            if self.graph_wrapper.is_start(&cur) {
+            } else {
+                self.graph_wrapper.seen_for_point(&cur);
+            }
+
+            let neighbors = self
+                .graph_wrapper
+                .edges(&cur)
+                .into_iter()
+                .filter(|(to, _)| !self.graph_wrapper.is_reached(to))
+                .map(|(to, move_length)| (to, length + move_length))
+                .collect::<Vec<_>>();
+
+            for (to, to_length) in neighbors {
+                self.graph_wrapper.set_came_from(&to, &cur, to_length);
+                self.graph_wrapper.emit(SearchEvent::Relaxed {
+                    from: cur.clone(),
+                    to: to.clone(),
+                    length: to_length,
+                });
+
+                // Found `end`
+                if self.graph_wrapper.is_end(&to) {
+                    self.graph_wrapper.reach_end(to);
+                    return;
+                }
+
+                let heuristic_length_end = distance_to_end(&self.graph_wrapper, &to);
+                reached_points.push((heuristic_length_end, to_length, to));
+            }
+        }
+
+        reached_points.sort();
+
+        for (_, _, pruned) in reached_points.split_off(self.beam_width.min(reached_points.len())) {
+            self.graph_wrapper.pruned_for_point(&pruned);
+        }
+        for (heuristic_length_end, _, to) in &reached_points {
+            self.graph_wrapper.emit(SearchEvent::Pushed {
+                node: to.clone(),
+                priority: *heuristic_length_end,
+            });
+        }
+
+        self.beam = reached_points
+            .into_iter()
+            .map(|(_, length, to)| (length, to))
+            .collect();
+    }
+
+    fn reset(&mut self) {
+        self.graph_wrapper.reset();
+
+        self.beam = Self::initial_beam(&self.graph_wrapper);
+    }
+
+    fn graph_wrapper(&self) -> &GraphWrapper {
+        &self.graph_wrapper
+    }
+
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper {
+        &mut self.graph_wrapper
+    }
+
+    fn bound(&self) -> Option<usize> {
+        Some(self.beam_width)
+    }
+
+    fn set_bound(&mut self, bound: usize) {
+        self.beam_width = bound;
+
+        self.reset();
+    }
+
+    fn heuristic(&self, node: &PointCoord) -> u32 {
+        distance_to_end(&self.graph_wrapper, node)
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        self.graph_wrapper.graph().distance_name()
+    }
+
+    // The whole beam is expanded by the next step
+    fn frontier(&self) -> Vec<FrontierNode<PointCoord>> {
+        self.beam
+            .iter()
+            .map(|(length, point_coord)| FrontierNode {
+                node: point_coord.clone(),
+                g: *length,
+                h: self.heuristic(point_coord),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algo::{
+            tests::{path_length, predefined_graphs},
+            AStar, Bfs,
+        },
+        graph::Point,
+    };
+
+    // Number of moves along the found path
+    fn moves_cnt(algo: &mut dyn PathFinder) -> Option<usize> {
+        path_length(algo)?;
+
+        algo.build_path().map(|path| path.points().len() - 1)
+    }
+
+    // The same graph with every obstacle cleared, breadth-first search enters obstacles then
+    fn without_obstacles(graph: &Graph) -> Arc<Graph> {
+        let mut graph = graph.clone();
+        for layer in 0..graph.layers() {
+            for y in 0..graph.n() {
+                for x in 0..graph.m() {
+                    let point_coord = PointCoord { x, y, layer };
+                    if matches!(graph.point_at(&point_coord), Point::Obstacle { .. }) {
+                        graph.set_point(&point_coord, Point::Free);
+                    }
+                }
+            }
+        }
+
+        Arc::new(graph)
+    }
+
+    // Beam search enters obstacles at their cost, breadth-first search doesn't
+    #[test]
+    fn unbounded_beam_is_breadth_first() {
+        for graph in predefined_graphs() {
+            let a_star_length = path_length(AStar::new(graph.clone()).as_mut()).unwrap();
+            let mut beam_search = BeamSearch::new(graph.clone());
+            beam_search.set_bound(usize::MAX);
+
+            assert_eq!(
+                moves_cnt(beam_search.as_mut()),
+                moves_cnt(Bfs::new(without_obstacles(&graph)).as_mut())
+            );
+            assert!(beam_search.build_path().unwrap().length() >= a_star_length);
+        }
+    }
+
+    #[test]
+    fn narrow_beam_is_never_shorter_than_a_star() {
+        for graph in predefined_graphs() {
+            let a_star_length = path_length(AStar::new(graph.clone()).as_mut()).unwrap();
+
+            for beam_width in 1..=BeamSearch::BEAM_WIDTH {
+                let mut beam_search = BeamSearch::new(graph.clone());
+                beam_search.set_bound(beam_width);
+
+                if let Some(length) = path_length(beam_search.as_mut()) {
+                    assert!(length >= a_star_length);
+                }
+            }
+        }
+    }
+}
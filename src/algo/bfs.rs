@@ -1,19 +1,30 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, sync::Arc};
 
-use crate::graph::{Graph, GraphWrapper, Point, PointCoord};
+use crate::graph::{Graph, GraphWrapper, SearchEvent, SearchGraph};
 
-use super::PathFinder;
+use super::{FrontierNode, PathFinder};
 
-pub struct Bfs {
-    graph_wrapper: GraphWrapper,
+pub struct Bfs<G: SearchGraph = Graph> {
+    graph_wrapper: GraphWrapper<G>,
 
-    frontier: VecDeque<(u32, PointCoord)>,
+    frontier: VecDeque<(u32, G::Node)>,
 }
 
-impl PathFinder for Bfs {
-    fn new(graph: Graph) -> Box<dyn PathFinder> {
+impl<G: SearchGraph> Bfs<G> {
+    fn initial_frontier(graph_wrapper: &GraphWrapper<G>) -> VecDeque<(u32, G::Node)> {
+        VecDeque::from_iter(
+            graph_wrapper
+                .start_coords()
+                .iter()
+                .map(|start_coord| (0, start_coord.clone())),
+        )
+    }
+}
+
+impl<G: SearchGraph> PathFinder<G> for Bfs<G> {
+    fn new(graph: Arc<G>) -> Box<dyn PathFinder<G>> {
         let graph_wrapper = GraphWrapper::new(graph);
-        let frontier = VecDeque::from_iter([(0, graph_wrapper.start_coord().clone())]);
+        let frontier = Self::initial_frontier(&graph_wrapper);
 
         println!("[I] Bfs");
 
@@ -23,59 +34,112 @@ impl PathFinder for Bfs {
         })
     }
 
+    fn name(&self) -> &'static str {
+        "Bfs"
+    }
+
     fn step(&mut self) {
-        let mut result = false;
+        let mut reached = None;
 
         if let Some((length, cur)) = self.frontier.pop_front() {
+            self.graph_wrapper.emit(SearchEvent::Popped {
+                node: cur.clone(),
+                priority: length,
+            });
+
             // Mark current as seen
-            if &cur == self.graph_wrapper.start_coord() {
+            if self.graph_wrapper.is_start(&cur) {
             } else {
                 self.graph_wrapper.seen_for_point(&cur);
             }
 
-            // Bfs doest support lengths(weights)
+            // Bfs doest support lengths(weights), every move is counted as 1
+            // and obstacles are not entered
             let to_length = length + 1;
 
+            let graph = self.graph_wrapper.graph();
             let reached_points = self
                 .graph_wrapper
-                .graph()
-                .neighbors(&cur)
-                .filter_map(|(to_point, to)| match to_point {
-                    &Point::Free if self.graph_wrapper.came_from[to.y][to.x] == None => {
-                        Some((to, cur.clone(), to_length))
-                    }
-
-                    &Point::End => {
-                        result = true;
-                        Some((to, cur.clone(), to_length))
-                    }
-                    _ => None,
-                })
+                .edges(&cur)
+                .into_iter()
+                .filter(|(to, _)| graph.is_passable(to) && !self.graph_wrapper.is_reached(to))
                 .collect::<Vec<_>>();
 
-            for (to, cur, to_length) in reached_points.into_iter() {
+            for (to, _) in reached_points.into_iter() {
+                if self.graph_wrapper.is_end(&to) {
+                    reached.get_or_insert(to.clone());
+                }
+
                 self.frontier.push_back((to_length, to.clone()));
-                self.graph_wrapper.came_from[to.y][to.x] = Some((cur.clone(), to_length));
+                self.graph_wrapper.set_came_from(&to, &cur, to_length);
+                self.graph_wrapper.emit(SearchEvent::Relaxed {
+                    from: cur.clone(),
+                    to: to.clone(),
+                    length: to_length,
+                });
+                self.graph_wrapper.emit(SearchEvent::Pushed {
+                    node: to,
+                    priority: to_length,
+                });
             }
-        };
-
-        if result {
+        } else {
+            // Nothing left to expand, `end` can't be reached
             self.graph_wrapper.completed = true;
+        }
+
+        if let Some(end_coord) = reached {
+            self.graph_wrapper.reach_end(end_coord);
         };
     }
 
     fn reset(&mut self) {
         self.graph_wrapper.reset();
 
-        let frontier = VecDeque::from_iter([(0, self.graph_wrapper.start_coord().clone())]);
-        self.frontier = frontier;
+        self.frontier = Self::initial_frontier(&self.graph_wrapper);
     }
 
-    fn graph_wrapper<'a>(&'a self) -> &'a GraphWrapper {
+    fn graph_wrapper(&self) -> &GraphWrapper<G> {
         &self.graph_wrapper
     }
 
-    fn graph_wrapper_mut<'a>(&'a mut self) -> &'a mut GraphWrapper {
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper<G> {
         &mut self.graph_wrapper
     }
+
+    fn frontier(&self) -> Vec<FrontierNode<G::Node>> {
+        self.frontier
+            .iter()
+            .map(|(length, node)| FrontierNode {
+                node: node.clone(),
+                g: *length,
+                h: 0,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algo::tests::{path_length, predefined_graphs},
+        graph::Point,
+    };
+
+    #[test]
+    fn path_goes_around_obstacles() {
+        for graph in predefined_graphs() {
+            let mut bfs = Bfs::new(graph.clone());
+            let Some(length) = path_length(bfs.as_mut()) else {
+                continue;
+            };
+            let path = bfs.build_path().unwrap();
+
+            assert_eq!(length as usize, path.points().len() - 1);
+            assert!(path
+                .points()
+                .iter()
+                .all(|point_coord| !matches!(graph.point_at(point_coord), Point::Obstacle { .. })));
+        }
+    }
 }
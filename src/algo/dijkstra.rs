@@ -1,20 +1,116 @@
-use std::{cmp::Reverse, collections::BinaryHeap};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::Arc,
+};
 
-use crate::graph::{GraphWrapper, PointCoord};
+use crate::graph::{GraphWrapper, PointCoord, SearchEvent, SearchGraph};
 
-use super::{Graph, PathFinder, Point};
+use super::{is_live, FrontierNode, Graph, PathFinder};
 
-pub struct Dijkstra {
-    graph_wrapper: GraphWrapper,
+pub struct Dijkstra<G: SearchGraph = Graph> {
+    graph_wrapper: GraphWrapper<G>,
 
-    priority_queue: BinaryHeap<(Reverse<u32>, PointCoord)>,
+    priority_queue: BinaryHeap<(Reverse<u32>, G::Node)>,
 }
 
-impl PathFinder for Dijkstra {
-    fn new(graph: Graph) -> Box<dyn PathFinder> {
+impl<G: SearchGraph> Dijkstra<G> {
+    fn initial_priority_queue(
+        graph_wrapper: &GraphWrapper<G>,
+    ) -> BinaryHeap<(Reverse<u32>, G::Node)> {
+        BinaryHeap::from_iter(
+            graph_wrapper
+                .start_coords()
+                .iter()
+                .map(|start_coord| (Reverse(0), start_coord.clone())),
+        )
+    }
+}
+
+impl Dijkstra {
+    // Shortest path from any of `from` to the nearest of `to` on the unmarked graph,
+    // moves for which `is_blocked` holds are not taken, the events go to `on_event`.
+    // Returns the path, its length and the expanded points.
+    pub fn shortest_path(
+        graph: &Graph,
+        from: &[PointCoord],
+        to: &[PointCoord],
+        is_blocked: impl Fn(&PointCoord, &PointCoord) -> bool,
+        mut on_event: impl FnMut(SearchEvent<PointCoord>),
+    ) -> (Option<(Vec<PointCoord>, u32)>, Vec<PointCoord>) {
+        let mut came_from = HashMap::<PointCoord, (PointCoord, u32)>::from_iter(
+            from.iter()
+                .map(|from_coord| (from_coord.clone(), (from_coord.clone(), 0))),
+        );
+        let mut priority_queue = BinaryHeap::from_iter(
+            from.iter()
+                .map(|from_coord| (Reverse(0), from_coord.clone())),
+        );
+        let mut seen = vec![];
+
+        while let Some((Reverse(length), cur)) = priority_queue.pop() {
+            // Skip `worse` points
+            if came_from[&cur].1 < length {
+                on_event(SearchEvent::SkippedStale { node: cur });
+                continue;
+            }
+            on_event(SearchEvent::Popped {
+                node: cur.clone(),
+                priority: length,
+            });
+
+            if to.contains(&cur) {
+                on_event(SearchEvent::GoalReached {
+                    node: cur.clone(),
+                    length,
+                });
+
+                let mut path = vec![cur];
+                while !from.contains(path.last().unwrap()) {
+                    path.push(came_from[path.last().unwrap()].0.clone());
+                }
+                path.reverse();
+
+                return (Some((path, length)), seen);
+            }
+
+            for (to_point, to_coord) in graph.neighbors(&cur) {
+                if is_blocked(&cur, &to_coord) {
+                    continue;
+                }
+
+                let Some(move_length) = graph.move_length(&cur, &to_coord, &to_point) else {
+                    continue;
+                };
+                let length_to = length + move_length;
+
+                if !matches!(came_from.get(&to_coord), Some((_, length_best)) if *length_best <= length_to)
+                {
+                    priority_queue.push((Reverse(length_to), to_coord.clone()));
+                    came_from.insert(to_coord.clone(), (cur.clone(), length_to));
+                    on_event(SearchEvent::Relaxed {
+                        from: cur.clone(),
+                        to: to_coord.clone(),
+                        length: length_to,
+                    });
+                    on_event(SearchEvent::Pushed {
+                        node: to_coord,
+                        priority: length_to,
+                    });
+                }
+            }
+
+            seen.push(cur);
+        }
+
+        (None, seen)
+    }
+}
+
+impl<G: SearchGraph> PathFinder<G> for Dijkstra<G> {
+    fn new(graph: Arc<G>) -> Box<dyn PathFinder<G>> {
         let graph_wrapper = GraphWrapper::new(graph);
-        let priority_queue =
-            BinaryHeap::from_iter([(Reverse(0), graph_wrapper.start_coord().clone())]);
+        let priority_queue = Self::initial_priority_queue(&graph_wrapper);
 
         println!("[I] Dijkstra");
 
@@ -24,79 +120,104 @@ impl PathFinder for Dijkstra {
         })
     }
 
+    fn name(&self) -> &'static str {
+        "Dijkstra"
+    }
+
     fn step(&mut self) {
-        let mut result = false;
+        let mut reached = None;
 
         while let Some((Reverse(length), cur)) = self.priority_queue.pop() {
             // Skip `worse` points
-            if let Some((_, length_best)) = self.graph_wrapper.came_from[cur.y][cur.x] {
+            if let Some((_, length_best)) = self.graph_wrapper.came_from(&cur) {
                 if length_best < length {
+                    self.graph_wrapper
+                        .emit(SearchEvent::SkippedStale { node: cur });
                     continue;
                 }
             }
-
-            // Found `end`
-            if &cur == self.graph_wrapper.end_coord() {
-                result = true;
+            self.graph_wrapper.emit(SearchEvent::Popped {
+                node: cur.clone(),
+                priority: length,
+            });
+
+            // Found the nearest `end`
+            if self.graph_wrapper.is_end(&cur) {
+                reached = Some(cur);
                 break;
             }
 
             // Mark current as seen, not mark start
-            if &cur == self.graph_wrapper.start_coord() {
+            if self.graph_wrapper.is_start(&cur) {
             } else {
                 self.graph_wrapper.seen_for_point(&cur);
             }
 
             let reached_points = self
                 .graph_wrapper
-                .graph()
-                .neighbors(&cur)
-                .filter_map(|(to_point, to)| {
-                    let length_to = match to_point {
-                        Point::Free => length + 1,
-                        Point::End => length + 1,
-                        Point::Obstacle {
-                            length: point_length,
-                        } => {
-                            assert!(point_length < &4);
-                            length + (point_length + 1) * Graph::OBSTACLE_DIFFICULTY_K
-                        }
-                        _ => return None,
-                    };
-
-                    match self.graph_wrapper.came_from[to.y][to.x] {
+                .edges(&cur)
+                .into_iter()
+                .filter_map(|(to, move_length)| {
+                    let length_to = length + move_length;
+
+                    match self.graph_wrapper.came_from(&to) {
                         Some((_, length_best)) if length_best <= length_to => None,
-                        _ => Some((to, cur.clone(), length_to)),
+                        _ => Some((to, length_to)),
                     }
                 })
                 .collect::<Vec<_>>();
 
-            for (to, cur, length_to) in reached_points.into_iter() {
+            for (to, length_to) in reached_points.into_iter() {
                 self.priority_queue.push((Reverse(length_to), to.clone()));
-                self.graph_wrapper.came_from[to.y][to.x] = Some((cur, length_to));
+                self.graph_wrapper.set_came_from(&to, &cur, length_to);
+                self.graph_wrapper.emit(SearchEvent::Relaxed {
+                    from: cur.clone(),
+                    to: to.clone(),
+                    length: length_to,
+                });
+                self.graph_wrapper.emit(SearchEvent::Pushed {
+                    node: to,
+                    priority: length_to,
+                });
             }
 
-            break;
+            return;
         }
 
-        if result {
-            self.graph_wrapper.completed = true;
-        };
+        match reached {
+            Some(end_coord) => self.graph_wrapper.reach_end(end_coord),
+            // Nothing left to expand, `end` can't be reached
+            None => self.graph_wrapper.completed = true,
+        }
     }
 
     fn reset(&mut self) {
         self.graph_wrapper.reset();
 
-        let priority_queue =
-            BinaryHeap::from_iter([(Reverse(0), self.graph_wrapper.start_coord().clone())]);
-        self.priority_queue = priority_queue;
+        self.priority_queue = Self::initial_priority_queue(&self.graph_wrapper);
     }
 
-    fn graph_wrapper<'a>(&'a self) -> &'a GraphWrapper {
+    fn graph_wrapper(&self) -> &GraphWrapper<G> {
         &self.graph_wrapper
     }
 
-    fn graph_wrapper_mut<'a>(&'a mut self) -> &'a mut GraphWrapper {
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper<G> {
         &mut self.graph_wrapper
     }
+
+    fn frontier(&self) -> Vec<FrontierNode<G::Node>> {
+        // Popped from the greatest one
+        let mut entries = self.priority_queue.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+
+        entries
+            .into_iter()
+            .filter(|(Reverse(length), node)| is_live(&self.graph_wrapper, node, *length))
+            .map(|(Reverse(length), node)| FrontierNode {
+                node: node.clone(),
+                g: *length,
+                h: 0,
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,277 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use crate::graph::{Graph, GraphWrapper, Mark, PointCoord, SearchEvent, SearchGraph};
+
+use super::{distance_to_end, FrontierNode, PathFinder};
+
+// A* keeping at most `max_frontier` points in the frontier, SMA*-like, though every
+// expanded point stays in memory. The worst ones are forgotten, their parents remember
+// the best forgotten `f` and get back to the frontier to regenerate them once there
+// is room for it.
+pub struct FrontierBoundedAStar {
+    graph_wrapper: GraphWrapper,
+
+    max_frontier: usize,
+    // (f, g, point), the first is the best one
+    frontier: BTreeSet<(u32, u32, PointCoord)>,
+    // Parents of forgotten points with the best forgotten `f`
+    forgotten: HashMap<PointCoord, u32>,
+}
+
+impl FrontierBoundedAStar {
+    pub const MAX_FRONTIER: usize = 8;
+
+    fn initial_frontier(graph_wrapper: &GraphWrapper) -> BTreeSet<(u32, u32, PointCoord)> {
+        graph_wrapper
+            .start_coords()
+            .iter()
+            .map(|start_coord| (0, 0, start_coord.clone()))
+            .collect()
+    }
+
+    fn is_live(&self, (_, length, point_coord): &(u32, u32, PointCoord)) -> bool {
+        matches!(
+            self.graph_wrapper.came_from(point_coord),
+            Some((_, length_best)) if length_best == *length
+        )
+    }
+
+    // Drops the worst points until the frontier fits into `max_frontier`. Points which
+    // can't be forgotten are kept beyond it.
+    fn prune(&mut self) {
+        let mut kept = vec![];
+
+        while self.frontier.len() > self.max_frontier {
+            let Some(worst) = self.frontier.pop_last() else {
+                break;
+            };
+            if !self.is_live(&worst) {
+                continue;
+            }
+
+            // Starts are never forgotten, there is nothing to regenerate them from.
+            // Nor are points expanded through a longer path, their successors lead
+            // through them and get the shorter path once they are expanded again.
+            if self.graph_wrapper.is_start(&worst.2)
+                || self.graph_wrapper.mark(&worst.2) == Mark::Seen
+            {
+                kept.push(worst);
+                continue;
+            }
+
+            let (f, _, pruned) = worst;
+            let (parent, _) = self.graph_wrapper.remove_came_from(&pruned).unwrap();
+
+            if !self.graph_wrapper.is_end(&pruned) {
+                self.graph_wrapper.pruned_for_point(&pruned);
+            }
+
+            let forgotten_f = self.forgotten.entry(parent).or_insert(f);
+            *forgotten_f = (*forgotten_f).min(f);
+        }
+
+        self.frontier.extend(kept);
+    }
+
+    // Brings back parents of forgotten points while there is room for them,
+    // or while they are better than the whole frontier
+    fn restore(&mut self) {
+        while let Some((parent, f)) = self
+            .forgotten
+            .iter()
+            .min_by_key(|(parent, f)| (**f, *parent))
+            .map(|(parent, f)| (parent.clone(), *f))
+        {
+            let is_best = self
+                .frontier
+                .iter()
+                .find(|entry| self.is_live(entry))
+                .is_none_or(|(best_f, _, _)| f < *best_f);
+            if self.frontier.len() >= self.max_frontier && !is_best {
+                break;
+            }
+            self.forgotten.remove(&parent);
+
+            if let Some((_, length)) = self.graph_wrapper.came_from(&parent) {
+                self.frontier.insert((f, length, parent.clone()));
+                self.graph_wrapper.emit(SearchEvent::Pushed {
+                    node: parent,
+                    priority: f,
+                });
+            }
+        }
+    }
+}
+
+impl PathFinder for FrontierBoundedAStar {
+    fn new(graph: Arc<Graph>) -> Box<dyn PathFinder>
+    where
+        Self: Sized,
+    {
+        let graph_wrapper = GraphWrapper::new(graph);
+        let frontier = Self::initial_frontier(&graph_wrapper);
+
+        println!(
+            "[I] FrontierBoundedAStar[Heuristic: {}, max frontier: {}]",
+            graph_wrapper.graph().distance_name(),
+            Self::MAX_FRONTIER
+        );
+
+        Box::new(Self {
+            graph_wrapper,
+            max_frontier: Self::MAX_FRONTIER,
+            frontier,
+            forgotten: HashMap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "FrontierBoundedAStar"
+    }
+
+    fn step(&mut self) {
+        self.restore();
+
+        while let Some(best) = self.frontier.pop_first() {
+            // Skip `worse` and forgotten points
+            if !self.is_live(&best) {
+                let (_, _, stale) = best;
+                self.graph_wrapper
+                    .emit(SearchEvent::SkippedStale { node: stale });
+                continue;
+            }
+            let (f, length, cur) = best;
+            self.graph_wrapper.emit(SearchEvent::Popped {
+                node: cur.clone(),
+                priority: f,
+            });
+
+            // Found the nearest `end`
+            if self.graph_wrapper.is_end(&cur) {
+                self.graph_wrapper.reach_end(cur);
+                return;
+            }
+
+            // Mark current as seen once, not mark start. A point reached by a shorter path
+            // after it was expanded is expanded again.
+            if !self.graph_wrapper.is_start(&cur) && self.graph_wrapper.mark(&cur) != Mark::Seen {
+                self.graph_wrapper.seen_for_point(&cur);
+            }
+
+            // Forgotten points are regenerated, so pruned ones are entered again
+            let reached_points = self
+                .graph_wrapper
+                .graph()
+                .edges(&cur)
+                .into_iter()
+                .filter_map(|(to, move_length)| {
+                    let length_to = length + move_length;
+
+                    match self.graph_wrapper.came_from(&to) {
+                        Some((_, length_best)) if length_best <= length_to => None,
+                        _ => Some((to, length_to)),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            for (to, length_to) in reached_points.into_iter() {
+                let heuristic_length_end = distance_to_end(&self.graph_wrapper, &to);
+                let f = length_to + heuristic_length_end;
+
+                self.frontier.insert((f, length_to, to.clone()));
+                self.graph_wrapper.set_came_from(&to, &cur, length_to);
+                self.graph_wrapper.emit(SearchEvent::Relaxed {
+                    from: cur.clone(),
+                    to: to.clone(),
+                    length: length_to,
+                });
+                self.graph_wrapper.emit(SearchEvent::Pushed {
+                    node: to,
+                    priority: f,
+                });
+            }
+
+            self.prune();
+
+            return;
+        }
+
+        // Nothing left to expand or to regenerate
+        if self.forgotten.is_empty() {
+            self.graph_wrapper.completed = true;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.graph_wrapper.reset();
+
+        self.frontier = Self::initial_frontier(&self.graph_wrapper);
+        self.forgotten = HashMap::new();
+    }
+
+    fn graph_wrapper(&self) -> &GraphWrapper {
+        &self.graph_wrapper
+    }
+
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper {
+        &mut self.graph_wrapper
+    }
+
+    fn bound(&self) -> Option<usize> {
+        Some(self.max_frontier)
+    }
+
+    fn set_bound(&mut self, bound: usize) {
+        self.max_frontier = bound;
+
+        self.reset();
+    }
+
+    fn heuristic(&self, node: &PointCoord) -> u32 {
+        distance_to_end(&self.graph_wrapper, node)
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        self.graph_wrapper.graph().distance_name()
+    }
+
+    // `f` of a parent brought back for its forgotten points is theirs, `h` includes the difference
+    fn frontier(&self) -> Vec<FrontierNode<PointCoord>> {
+        self.frontier
+            .iter()
+            .filter(|entry| self.is_live(entry))
+            .map(|(f, length, point_coord)| FrontierNode {
+                node: point_coord.clone(),
+                g: *length,
+                h: f - length,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::{
+        tests::{path_length, predefined_graphs},
+        AStar,
+    };
+
+    #[test]
+    fn finds_shortest_path_with_bounded_frontier() {
+        for graph in predefined_graphs() {
+            let a_star_length = path_length(AStar::new(graph.clone()).as_mut());
+            assert!(a_star_length.is_some());
+
+            for max_frontier in [1, FrontierBoundedAStar::MAX_FRONTIER, usize::MAX] {
+                let mut frontier_bounded_a_star = FrontierBoundedAStar::new(graph.clone());
+                frontier_bounded_a_star.set_bound(max_frontier);
+
+                assert_eq!(path_length(frontier_bounded_a_star.as_mut()), a_star_length);
+            }
+        }
+    }
+}
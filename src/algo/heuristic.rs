@@ -1,38 +1,37 @@
-use std::{cmp::Reverse, collections::BinaryHeap};
+use std::{cmp::Reverse, collections::BinaryHeap, sync::Arc};
 
-use crate::graph::{GraphWrapper, Point, PointCoord};
+use crate::graph::{Graph, GraphWrapper, SearchEvent, SearchGraph};
 
-use super::PathFinder;
+use super::{distance_to_end, is_live, FrontierNode, PathFinder};
 
-pub struct Heuristic {
-    graph_wrapper: GraphWrapper,
+pub struct Heuristic<G: SearchGraph = Graph> {
+    graph_wrapper: GraphWrapper<G>,
 
-    priority_queue: BinaryHeap<(Reverse<u32>, Reverse<u32>, PointCoord)>,
+    priority_queue: BinaryHeap<(Reverse<u32>, Reverse<u32>, G::Node)>,
 }
 
-impl Heuristic {
-    // Manhattan distance on a square grid
-    pub fn heuristic(&self, a: &PointCoord, b: &PointCoord) -> u32 {
-        let x_max = a.x.max(b.x);
-        let x_min = a.x.min(b.x);
-
-        let y_max = a.y.min(b.y);
-        let y_min = a.y.min(b.y);
-
-        return (x_max - x_min + y_max - y_min) as u32;
+impl<G: SearchGraph> Heuristic<G> {
+    fn initial_priority_queue(
+        graph_wrapper: &GraphWrapper<G>,
+    ) -> BinaryHeap<(Reverse<u32>, Reverse<u32>, G::Node)> {
+        BinaryHeap::from_iter(
+            graph_wrapper
+                .start_coords()
+                .iter()
+                .map(|start_coord| (Reverse(0), Reverse(0), start_coord.clone())),
+        )
     }
 }
 
-impl PathFinder for Heuristic {
-    fn new(graph: crate::graph::Graph) -> Box<dyn PathFinder>
+impl<G: SearchGraph> PathFinder<G> for Heuristic<G> {
+    fn new(graph: Arc<G>) -> Box<dyn PathFinder<G>>
     where
         Self: Sized,
     {
         let graph_wrapper = GraphWrapper::new(graph);
-        let priority_queue =
-            BinaryHeap::from_iter([(Reverse(0), Reverse(0), graph_wrapper.start_coord().clone())]);
+        let priority_queue = Self::initial_priority_queue(&graph_wrapper);
 
-        println!("[I] Heuristic[Manhattan distance]");
+        println!("[I] Heuristic[{}]", graph_wrapper.graph().heuristic_name());
 
         Box::new(Self {
             graph_wrapper,
@@ -40,49 +39,56 @@ impl PathFinder for Heuristic {
         })
     }
 
+    fn name(&self) -> &'static str {
+        "Heuristic"
+    }
+
     fn step(&mut self) {
-        let mut result = false;
+        let mut reached = None;
 
-        while let Some((Reverse(_), Reverse(length), cur)) = self.priority_queue.pop() {
+        while let Some((Reverse(priority), Reverse(length), cur)) = self.priority_queue.pop() {
             // Skip `worse` points
-            if let Some((_, length_best)) = self.graph_wrapper.came_from[cur.y][cur.x] {
+            if let Some((_, length_best)) = self.graph_wrapper.came_from(&cur) {
                 if length_best < length {
+                    self.graph_wrapper
+                        .emit(SearchEvent::SkippedStale { node: cur });
                     continue;
                 }
             }
-
-            // Found `end`
-            if &cur == self.graph_wrapper.end_coord() {
-                result = true;
+            self.graph_wrapper.emit(SearchEvent::Popped {
+                node: cur.clone(),
+                priority,
+            });
+
+            // Found the nearest `end`
+            if self.graph_wrapper.is_end(&cur) {
+                reached = Some(cur);
                 break;
             }
 
             // Mark current as seen, not mark start
-            if &cur == self.graph_wrapper.start_coord() {
+            if self.graph_wrapper.is_start(&cur) {
             } else {
                 self.graph_wrapper.seen_for_point(&cur);
             }
 
             let reached_points = self
                 .graph_wrapper
-                .graph()
-                .neighbors(&cur)
-                .filter_map(|(to_point, to)| {
-                    let length_to = match to_point {
-                        Point::Free => length + 1,
-                        Point::End => length + 1,
-                        _ => return None,
-                    };
-
-                    match self.graph_wrapper.came_from[to.y][to.x] {
+                .edges(&cur)
+                .into_iter()
+                .filter_map(|(to, move_length)| {
+                    let length_to = length + move_length;
+
+                    match self.graph_wrapper.came_from(&to) {
                         Some((_, length_best)) if length_best <= length_to => None,
-                        _ => Some((to, cur.clone(), length_to)),
+                        _ => Some((to, length_to)),
                     }
                 })
                 .collect::<Vec<_>>();
 
-            for (to, cur, length_to) in reached_points.into_iter() {
-                let heuristic_length_end = self.heuristic(&to, &self.graph().end().unwrap());
+            for (to, length_to) in reached_points.into_iter() {
+                // Admissible for the nearest of several `end`s
+                let heuristic_length_end = distance_to_end(&self.graph_wrapper, &to);
 
                 self.priority_queue.push((
                     Reverse(heuristic_length_end),
@@ -90,33 +96,63 @@ impl PathFinder for Heuristic {
                     to.clone(),
                 ));
 
-                self.graph_wrapper.came_from[to.y][to.x] = Some((cur, length_to));
+                self.graph_wrapper.set_came_from(&to, &cur, length_to);
+                self.graph_wrapper.emit(SearchEvent::Relaxed {
+                    from: cur.clone(),
+                    to: to.clone(),
+                    length: length_to,
+                });
+                self.graph_wrapper.emit(SearchEvent::Pushed {
+                    node: to,
+                    priority: heuristic_length_end,
+                });
             }
 
-            break;
+            return;
         }
 
-        if result {
-            self.graph_wrapper.completed = true;
-        };
+        match reached {
+            Some(end_coord) => self.graph_wrapper.reach_end(end_coord),
+            // Nothing left to expand, `end` can't be reached
+            None => self.graph_wrapper.completed = true,
+        }
     }
 
     fn reset(&mut self) {
         self.graph_wrapper.reset();
 
-        let priority_queue = BinaryHeap::from_iter([(
-            Reverse(0),
-            Reverse(0),
-            self.graph_wrapper.start_coord().clone(),
-        )]);
-        self.priority_queue = priority_queue;
+        self.priority_queue = Self::initial_priority_queue(&self.graph_wrapper);
     }
 
-    fn graph_wrapper<'a>(&'a self) -> &'a GraphWrapper {
+    fn graph_wrapper(&self) -> &GraphWrapper<G> {
         &self.graph_wrapper
     }
 
-    fn graph_wrapper_mut<'a>(&'a mut self) -> &'a mut GraphWrapper {
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper<G> {
         &mut self.graph_wrapper
     }
+
+    fn heuristic(&self, node: &G::Node) -> u32 {
+        distance_to_end(&self.graph_wrapper, node)
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        self.graph_wrapper.graph().heuristic_name()
+    }
+
+    fn frontier(&self) -> Vec<FrontierNode<G::Node>> {
+        // Popped from the greatest one
+        let mut entries = self.priority_queue.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+
+        entries
+            .into_iter()
+            .filter(|(_, Reverse(length), node)| is_live(&self.graph_wrapper, node, *length))
+            .map(|(Reverse(h), Reverse(length), node)| FrontierNode {
+                node: node.clone(),
+                g: *length,
+                h: *h,
+            })
+            .collect()
+    }
 }
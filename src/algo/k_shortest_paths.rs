@@ -0,0 +1,226 @@
+use std::{cmp::Reverse, collections::BinaryHeap, sync::Arc};
+
+use crate::graph::{Graph, GraphPath, GraphWrapper, Mark, PointCoord};
+
+use super::{Dijkstra, PathFinder};
+
+// Yen's k shortest loopless paths. Every step finds the next shortest path.
+pub struct KShortestPaths {
+    graph_wrapper: GraphWrapper,
+
+    k: usize,
+
+    // Found paths ranked by length
+    paths: Vec<(Vec<PointCoord>, u32)>,
+    candidates: BinaryHeap<(Reverse<u32>, Reverse<Vec<PointCoord>>)>,
+}
+
+impl KShortestPaths {
+    pub const K: usize = 4;
+
+    fn path_length(graph: &Graph, path: &[PointCoord]) -> u32 {
+        path.windows(2)
+            .map(|step| {
+                let to_point = graph.point_at(&step[1]);
+
+                graph
+                    .move_length(&step[0], &step[1], &to_point)
+                    .expect("Path goes through a point that can't be entered")
+            })
+            .sum()
+    }
+
+    fn shortest_path(
+        &mut self,
+        from: &[PointCoord],
+        is_blocked: impl Fn(&PointCoord, &PointCoord) -> bool,
+    ) -> Option<(Vec<PointCoord>, u32)> {
+        let mut events = vec![];
+        let (path, seen) = Dijkstra::shortest_path(
+            self.graph_wrapper.graph(),
+            from,
+            self.graph_wrapper.end_coords(),
+            is_blocked,
+            |event| events.push(event),
+        );
+        for event in events {
+            self.graph_wrapper.emit(event);
+        }
+
+        // Mark expanded points as seen once over all the searches
+        for point_coord in seen {
+            if self.graph_wrapper.point_at(&point_coord).is_on_the_way()
+                && self.graph_wrapper.mark(&point_coord) == Mark::Unseen
+            {
+                self.graph_wrapper.seen_for_point(&point_coord);
+            }
+        }
+
+        path
+    }
+
+    fn push_candidate(&mut self, path: Vec<PointCoord>, length: u32) {
+        let known = self.paths.iter().any(|(known, _)| known == &path)
+            || self
+                .candidates
+                .iter()
+                .any(|(_, Reverse(known))| known == &path);
+
+        if !known {
+            self.candidates.push((Reverse(length), Reverse(path)));
+        }
+    }
+
+    fn find_candidates(&mut self) {
+        let (last_path, _) = self.paths.last().unwrap().clone();
+
+        // Several `start`s act as one source joined to each of them,
+        // deviating from it means beginning at a `start` no found path begins at
+        let unused_start_coords = self
+            .graph_wrapper
+            .start_coords()
+            .iter()
+            .filter(|start_coord| self.paths.iter().all(|(path, _)| &path[0] != *start_coord))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unused_start_coords.is_empty() {
+            if let Some((path, length)) = self.shortest_path(&unused_start_coords, |_, _| false) {
+                self.push_candidate(path, length);
+            }
+        }
+
+        for spur_index in 0..last_path.len() - 1 {
+            let root_path = &last_path[..=spur_index];
+            let spur_coord = &last_path[spur_index];
+
+            // Moves used by already found paths sharing the same root
+            let blocked_moves = self
+                .paths
+                .iter()
+                .filter(|(path, _)| path.len() > spur_index + 1 && path.starts_with(root_path))
+                .map(|(path, _)| (path[spur_index].clone(), path[spur_index + 1].clone()))
+                .collect::<Vec<_>>();
+            // Root points are not visited again to keep the path loopless
+            let blocked_points = &root_path[..spur_index];
+
+            let spur_path = self.shortest_path(std::slice::from_ref(spur_coord), |from, to| {
+                blocked_points.contains(to) || blocked_moves.contains(&(from.clone(), to.clone()))
+            });
+
+            if let Some((spur_path, spur_length)) = spur_path {
+                let root_length = Self::path_length(self.graph_wrapper.graph(), root_path);
+
+                let mut path = root_path.to_vec();
+                path.extend(spur_path.into_iter().skip(1));
+
+                self.push_candidate(path, root_length + spur_length);
+            }
+        }
+    }
+}
+
+impl PathFinder for KShortestPaths {
+    fn new(graph: Arc<Graph>) -> Box<dyn PathFinder>
+    where
+        Self: Sized,
+    {
+        let graph_wrapper = GraphWrapper::new(graph);
+
+        println!("[I] KShortestPaths[Yen, k: {}]", Self::K);
+
+        Box::new(Self {
+            graph_wrapper,
+            k: Self::K,
+            paths: vec![],
+            candidates: BinaryHeap::new(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "KShortestPaths"
+    }
+
+    fn step(&mut self) {
+        if self.paths.is_empty() {
+            let start_coords = self.graph_wrapper.start_coords().to_vec();
+
+            match self.shortest_path(&start_coords, |_, _| false) {
+                Some(path) => self.paths.push(path),
+                None => self.graph_wrapper.completed = true,
+            }
+        } else {
+            self.find_candidates();
+
+            match self.candidates.pop() {
+                Some((Reverse(length), Reverse(path))) => self.paths.push((path, length)),
+                None => self.graph_wrapper.completed = true,
+            }
+        }
+
+        if self.paths.len() == self.k {
+            self.graph_wrapper.completed = true;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.graph_wrapper.reset();
+
+        self.paths = vec![];
+        self.candidates = BinaryHeap::new();
+    }
+
+    fn graph_wrapper(&self) -> &GraphWrapper {
+        &self.graph_wrapper
+    }
+
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper {
+        &mut self.graph_wrapper
+    }
+
+    fn build_path(&mut self) -> Option<GraphPath<'_>> {
+        if !self.is_completed() {
+            return None;
+        }
+
+        let (path, length) = self.paths.first()?.clone();
+
+        Some(self.graph_wrapper.build_path_along(path, length))
+    }
+
+    fn alternative_paths(&self) -> Vec<GraphPath<'_>> {
+        self.paths
+            .iter()
+            .map(|(path, length)| self.graph_wrapper.path_along(path.clone(), *length))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::tests::{path_length, predefined_graphs};
+
+    #[test]
+    fn finds_shortest_paths_ranked_by_length() {
+        for graph in predefined_graphs() {
+            let dijkstra_length = path_length(Dijkstra::new(graph.clone()).as_mut());
+            let mut k_shortest_paths = KShortestPaths::new(graph.clone());
+
+            assert_eq!(path_length(k_shortest_paths.as_mut()), dijkstra_length);
+
+            let paths = k_shortest_paths.alternative_paths();
+            assert!(!paths.is_empty() && paths.len() <= KShortestPaths::K);
+            for (i, path) in paths.iter().enumerate() {
+                let points = path.points();
+
+                assert_eq!(KShortestPaths::path_length(&graph, points), path.length());
+                // Loopless and found once
+                assert!((1..points.len()).all(|j| !points[..j].contains(&points[j])));
+                assert!(paths[..i].iter().all(|other| other.points() != points));
+            }
+            assert!(paths
+                .windows(2)
+                .all(|pair| pair[0].length() <= pair[1].length()));
+        }
+    }
+}
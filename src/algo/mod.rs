@@ -1,29 +1,72 @@
-use crate::graph::{Graph, GraphPath, GraphWrapper, Point, PointCoord};
+use std::sync::Arc;
+
+use crate::graph::{
+    Graph, GraphPath, GraphWrapper, ReservationTable, SearchGraph, SearchObserver, TimedPointCoord,
+};
 
 pub mod a_star;
+pub mod beam_search;
 pub mod bfs;
 pub mod dijkstra;
+pub mod frontier_bounded_a_star;
 pub mod heuristic;
+pub mod k_shortest_paths;
+pub mod space_time_a_star;
+pub mod time_dependent_a_star;
+pub mod waypoints;
 
 pub use a_star::AStar;
+pub use beam_search::BeamSearch;
 pub use bfs::Bfs;
 pub use dijkstra::Dijkstra;
+pub use frontier_bounded_a_star::FrontierBoundedAStar;
 pub use heuristic::Heuristic;
+pub use k_shortest_paths::KShortestPaths;
+pub use space_time_a_star::SpaceTimeAStar;
+pub use time_dependent_a_star::TimeDependentAStar;
+pub use waypoints::Waypoints;
+
+// Heuristic distance to the nearest of several `end`s, admissible for all of them.
+// 0 without any `end`, there is nothing to search then.
+fn distance_to_end<G: SearchGraph>(graph_wrapper: &GraphWrapper<G>, a: &G::Node) -> u32 {
+    graph_wrapper
+        .end_coords()
+        .iter()
+        .map(|end_coord| graph_wrapper.graph().heuristic(a, end_coord))
+        .min()
+        .unwrap_or(0)
+}
+
+// Frontier entry is stale once a shorter path to its node is found
+fn is_live<G: SearchGraph>(graph_wrapper: &GraphWrapper<G>, node: &G::Node, length: u32) -> bool {
+    matches!(graph_wrapper.came_from(node), Some((_, length_best)) if length_best == length)
+}
+
+// Node waiting in the frontier (open set) with the length of the path it was reached by (`g`)
+// and the estimate of the rest of the way to `end` (`h`)
+pub struct FrontierNode<N> {
+    pub node: N,
+    pub g: u32,
+    pub h: u32,
+}
 
-pub trait PathFinder {
-    fn new(graph: Graph) -> Box<dyn PathFinder>
+// Searches on the grid by default, the generic ones run on any `SearchGraph`
+pub trait PathFinder<G: SearchGraph = Graph> {
+    fn new(graph: Arc<G>) -> Box<dyn PathFinder<G>>
     where
         Self: Sized;
 
+    fn name(&self) -> &'static str;
+
     fn step(&mut self);
 
     fn reset(&mut self);
 
-    fn graph_wrapper<'a>(&'a self) -> &'a GraphWrapper;
+    fn graph_wrapper(&self) -> &GraphWrapper<G>;
 
-    fn graph_wrapper_mut<'a>(&'a mut self) -> &'a mut GraphWrapper;
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper<G>;
 
-    fn graph<'a>(&'a self) -> &'a Graph {
+    fn graph(&self) -> &G {
         self.graph_wrapper().graph()
     }
 
@@ -31,21 +74,159 @@ pub trait PathFinder {
         self.graph_wrapper().is_completed()
     }
 
-    fn point_at<'a>(&'a self, point_coord: &PointCoord) -> &'a Point {
-        self.graph_wrapper().point_at(point_coord)
+    // Replays the next recorded step if stepped back, makes and records a new one otherwise
+    fn step_forward(&mut self) {
+        if !self.graph_wrapper_mut().replay_step() {
+            self.graph_wrapper_mut().begin_step();
+            self.step();
+        }
+    }
+
+    fn step_back(&mut self) -> bool {
+        self.graph_wrapper_mut().step_back()
+    }
+
+    // Observers of the events of the search, they stay subscribed over resets
+    fn subscribe(&mut self, observer: Box<dyn SearchObserver<G::Node>>) {
+        self.graph_wrapper_mut().subscribe(observer);
     }
 
-    fn reset_with(&mut self, graph: Graph) {
-        *self.graph_wrapper_mut() = GraphWrapper::new(graph);
+    fn reset_with(&mut self, graph: Arc<G>) {
+        self.graph_wrapper_mut().rebuild(graph);
 
         self.reset();
     }
 
-    fn build_path<'a>(&'a mut self) -> Option<GraphPath<'a>> {
+    fn build_path(&mut self) -> Option<GraphPath<'_, G::Node>> {
         if self.is_completed() {
-            Some(self.graph_wrapper_mut().build_path())
+            self.graph_wrapper_mut().build_path()
         } else {
             None
         }
     }
+
+    // Path with the timestep of every move, only for time-aware searches
+    fn timed_path(&self) -> Option<&[TimedPointCoord]> {
+        None
+    }
+
+    fn reservation_table(&self) -> Option<&ReservationTable> {
+        None
+    }
+
+    // Paths ranked by length, only for searches finding several of them
+    fn alternative_paths(&self) -> Vec<GraphPath<'_, G::Node>> {
+        vec![]
+    }
+
+    // Waypoints in the visiting order, only for searches through waypoints
+    fn waypoints_order(&self) -> &[G::Node] {
+        &[]
+    }
+
+    // Estimate of the rest of the way from the node to `end` the search is guided by
+    fn heuristic(&self, _node: &G::Node) -> u32 {
+        0
+    }
+
+    // Name of the estimate above, uninformed searches have none
+    fn heuristic_name(&self) -> &'static str {
+        "None"
+    }
+
+    // Open set in the expanding order, the node expanded next first. Stale entries are left out,
+    // searches keeping no frontier between the steps have none.
+    fn frontier(&self) -> Vec<FrontierNode<G::Node>> {
+        vec![]
+    }
+
+    // Memory bound (beam width, stored nodes), only for bounded searches
+    fn bound(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_bound(&mut self, _bound: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Generation, Mark, PointCoord};
+
+    pub(super) fn predefined_graphs() -> Vec<Arc<Graph>> {
+        [
+            Generation::Predefined1,
+            Generation::Predefined2,
+            Generation::Predefined3,
+            Generation::Predefined4,
+            Generation::Predefined5,
+            Generation::Predefined6,
+            Generation::Predefined7,
+        ]
+        .into_iter()
+        .map(|generation| Arc::new(Graph::generate_graph(generation)))
+        .collect()
+    }
+
+    // Length of the found path once the algorithm completes
+    pub(super) fn path_length(algo: &mut dyn PathFinder) -> Option<u32> {
+        while !algo.is_completed() {
+            algo.step_forward();
+        }
+
+        algo.build_path().map(|path| path.length())
+    }
+
+    // Marks and parents of every point, the seen points, completion and the path
+    type Snapshot = (
+        Vec<(Mark, Option<(PointCoord, u32)>)>,
+        u32,
+        bool,
+        Vec<PointCoord>,
+    );
+
+    fn snapshot(algo: &dyn PathFinder) -> Snapshot {
+        let graph_wrapper = algo.graph_wrapper();
+        let graph = graph_wrapper.graph();
+        let points = (0..graph.node_count())
+            .map(|index| graph.node_at(index))
+            .map(|node| (graph_wrapper.mark(&node), graph_wrapper.came_from(&node)))
+            .collect();
+
+        (
+            points,
+            graph_wrapper.seen_points,
+            algo.is_completed(),
+            graph_wrapper.path().to_vec(),
+        )
+    }
+
+    #[test]
+    fn stepping_back_and_replaying_restores_every_step() {
+        for graph in predefined_graphs() {
+            for mut algo in [
+                AStar::new(graph.clone()),
+                FrontierBoundedAStar::new(graph.clone()),
+                BeamSearch::new(graph.clone()),
+            ] {
+                let mut snapshots = vec![snapshot(algo.as_ref())];
+                while !algo.is_completed() {
+                    algo.step_forward();
+                    snapshots.push(snapshot(algo.as_ref()));
+                }
+
+                for before in snapshots.iter().rev().skip(1) {
+                    assert!(algo.step_back());
+                    assert!(snapshot(algo.as_ref()) == *before);
+                }
+                assert!(!algo.step_back());
+
+                for after in snapshots.iter().skip(1) {
+                    algo.step_forward();
+                    assert!(snapshot(algo.as_ref()) == *after);
+                }
+                assert!(!algo.graph_wrapper().is_rewound());
+            }
+        }
+    }
 }
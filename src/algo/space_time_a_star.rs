@@ -0,0 +1,345 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::graph::{
+    Graph, GraphPath, GraphWrapper, Mark, PointCoord, ReservationTable, SearchEvent, SearchGraph,
+    TimedPointCoord,
+};
+
+use super::{FrontierNode, PathFinder};
+
+pub struct SpaceTimeAStar {
+    graph_wrapper: GraphWrapper,
+
+    reservation_table: ReservationTable,
+    search: SpaceTimeSearch,
+    timed_path: Vec<TimedPointCoord>,
+}
+
+enum SearchStep {
+    Expanded(PointCoord),
+    Skipped,
+    Reached(TimedPointCoord),
+    Exhausted,
+}
+
+// A* over `(PointCoord, t)` states. A move takes as many timesteps as its length
+// and the agent waits on the cell it leaves meanwhile, waiting in place takes one,
+// so `g` of a state is always its `t`.
+struct SpaceTimeSearch {
+    goals: Vec<PointCoord>,
+    // Reservations don't change after it, so a cell is expanded at most once past it
+    horizon: u32,
+    expanded_after_horizon: HashSet<PointCoord>,
+
+    priority_queue: BinaryHeap<(Reverse<u32>, Reverse<u32>, TimedPointCoord)>,
+    came_from: HashMap<TimedPointCoord, Option<TimedPointCoord>>,
+}
+
+impl SpaceTimeSearch {
+    fn new(
+        starts: &[PointCoord],
+        goals: &[PointCoord],
+        reservation_table: &ReservationTable,
+    ) -> Self {
+        let starts = starts.iter().map(|start| TimedPointCoord {
+            coord: start.clone(),
+            t: 0,
+        });
+
+        Self {
+            goals: goals.to_vec(),
+            horizon: reservation_table.horizon(),
+            expanded_after_horizon: HashSet::new(),
+            priority_queue: BinaryHeap::from_iter(
+                starts.clone().map(|start| (Reverse(0), Reverse(0), start)),
+            ),
+            came_from: HashMap::from_iter(starts.map(|start| (start, None))),
+        }
+    }
+
+    fn is_expanded_after_horizon(&self, timed_point: &TimedPointCoord) -> bool {
+        timed_point.t > self.horizon && self.expanded_after_horizon.contains(&timed_point.coord)
+    }
+
+    // Events are of the cells, a cell is pushed and popped again for every timestep
+    fn step(
+        &mut self,
+        graph: &Graph,
+        reservation_table: &ReservationTable,
+        mut on_event: impl FnMut(SearchEvent<PointCoord>),
+    ) -> SearchStep {
+        let Some((Reverse(priority), Reverse(t), cur)) = self.priority_queue.pop() else {
+            return SearchStep::Exhausted;
+        };
+
+        // Reached earlier with the same reservations ahead
+        if self.is_expanded_after_horizon(&cur) {
+            on_event(SearchEvent::SkippedStale { node: cur.coord });
+            return SearchStep::Skipped;
+        }
+        on_event(SearchEvent::Popped {
+            node: cur.coord.clone(),
+            priority,
+        });
+
+        // Found the nearest `end` and nobody is going to step on it later
+        if self.goals.contains(&cur.coord) && !reservation_table.is_reserved_since(&cur.coord, t) {
+            return SearchStep::Reached(cur);
+        }
+
+        if t > self.horizon {
+            self.expanded_after_horizon.insert(cur.coord.clone());
+        }
+
+        let waiting = (cur.coord.clone(), 1);
+        let reached_points = graph
+            .edges(&cur.coord)
+            .into_iter()
+            .chain([waiting])
+            .filter(|(to, length)| {
+                let arrival = t + length;
+
+                (t + 1..arrival).all(|t| !reservation_table.is_reserved(&cur.coord, t))
+                    && !reservation_table.is_reserved(to, arrival)
+                    && !reservation_table.is_move_reserved(&cur.coord, to, arrival - 1)
+            })
+            .map(|(to, length)| TimedPointCoord {
+                coord: to,
+                t: t + length,
+            })
+            .filter(|to| !self.came_from.contains_key(to) && !self.is_expanded_after_horizon(to))
+            .collect::<Vec<_>>();
+
+        for to in reached_points.into_iter() {
+            let heuristic_length_end = self
+                .goals
+                .iter()
+                .map(|goal| graph.moves(&to.coord, goal))
+                .min()
+                .unwrap();
+
+            let priority = to.t + heuristic_length_end;
+
+            self.priority_queue
+                .push((Reverse(priority), Reverse(to.t), to.clone()));
+            on_event(SearchEvent::Relaxed {
+                from: cur.coord.clone(),
+                to: to.coord.clone(),
+                length: to.t,
+            });
+            on_event(SearchEvent::Pushed {
+                node: to.coord.clone(),
+                priority,
+            });
+            self.came_from.insert(to, Some(cur.clone()));
+        }
+
+        SearchStep::Expanded(cur.coord)
+    }
+
+    fn timed_path(&self, reached: TimedPointCoord) -> Vec<TimedPointCoord> {
+        let mut timed_path = vec![reached.clone()];
+        let mut cur = reached;
+
+        while let Some(Some(prev)) = self.came_from.get(&cur) {
+            // The agent waits on `prev` until the move ends
+            timed_path.extend((prev.t + 1..cur.t).rev().map(|t| TimedPointCoord {
+                coord: prev.coord.clone(),
+                t,
+            }));
+            timed_path.push(prev.clone());
+            cur = prev.clone();
+        }
+        timed_path.reverse();
+
+        timed_path
+    }
+}
+
+impl SpaceTimeAStar {
+    // Plans a timed path from any of `starts` to the nearest of `goals`
+    // avoiding the reserved cells
+    pub fn plan(
+        graph: &Graph,
+        starts: &[PointCoord],
+        goals: &[PointCoord],
+        reservation_table: &ReservationTable,
+    ) -> Option<Vec<TimedPointCoord>> {
+        if starts.is_empty() || goals.is_empty() {
+            return None;
+        }
+
+        let mut search = SpaceTimeSearch::new(starts, goals, reservation_table);
+
+        loop {
+            match search.step(graph, reservation_table, |_| {}) {
+                SearchStep::Expanded(_) | SearchStep::Skipped => {}
+                SearchStep::Reached(reached) => return Some(search.timed_path(reached)),
+                SearchStep::Exhausted => return None,
+            }
+        }
+    }
+
+    // An oncoming agent travels from `end` to `start` and is planned first,
+    // so the searched agent has to give way to it
+    fn oncoming_reservations(graph: &Graph) -> ReservationTable {
+        let mut reservation_table = ReservationTable::default();
+
+        let oncoming_path = Self::plan(graph, &graph.ends(), &graph.starts(), &reservation_table);
+        if let Some(oncoming_path) = oncoming_path {
+            reservation_table.reserve_path(&oncoming_path);
+        }
+
+        reservation_table
+    }
+}
+
+impl PathFinder for SpaceTimeAStar {
+    fn new(graph: Arc<Graph>) -> Box<dyn PathFinder>
+    where
+        Self: Sized,
+    {
+        let reservation_table = Self::oncoming_reservations(&graph);
+        let graph_wrapper = GraphWrapper::new(graph);
+        let search = SpaceTimeSearch::new(
+            graph_wrapper.start_coords(),
+            graph_wrapper.end_coords(),
+            &reservation_table,
+        );
+
+        println!(
+            "[I] SpaceTimeAStar[Heuristic: {}]",
+            graph_wrapper.graph().distance_name()
+        );
+
+        Box::new(Self {
+            graph_wrapper,
+            reservation_table,
+            search,
+            timed_path: vec![],
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "SpaceTimeAStar"
+    }
+
+    fn step(&mut self) {
+        let mut events = vec![];
+        let search_step = self.search.step(
+            self.graph_wrapper.graph(),
+            &self.reservation_table,
+            |event| events.push(event),
+        );
+        for event in events {
+            self.graph_wrapper.emit(event);
+        }
+
+        match search_step {
+            SearchStep::Expanded(cur) => {
+                // Mark current as seen once, a cell is expanded again for every timestep
+                if self.graph_wrapper.point_at(&cur).is_on_the_way()
+                    && self.graph_wrapper.mark(&cur) == Mark::Unseen
+                {
+                    self.graph_wrapper.seen_for_point(&cur);
+                }
+            }
+            SearchStep::Skipped => {}
+            SearchStep::Reached(reached) => {
+                self.graph_wrapper.emit(SearchEvent::GoalReached {
+                    node: reached.coord.clone(),
+                    length: reached.t,
+                });
+
+                self.timed_path = self.search.timed_path(reached);
+                self.graph_wrapper.completed = true;
+            }
+            // Nothing left to expand, `end` can't be reached
+            SearchStep::Exhausted => self.graph_wrapper.completed = true,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.graph_wrapper.reset();
+
+        self.timed_path = vec![];
+        self.search = SpaceTimeSearch::new(
+            self.graph_wrapper.start_coords(),
+            self.graph_wrapper.end_coords(),
+            &self.reservation_table,
+        );
+    }
+
+    fn reset_with(&mut self, graph: Arc<Graph>) {
+        self.reservation_table = Self::oncoming_reservations(&graph);
+        self.graph_wrapper.rebuild(graph);
+
+        self.reset();
+    }
+
+    fn graph_wrapper(&self) -> &GraphWrapper {
+        &self.graph_wrapper
+    }
+
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper {
+        &mut self.graph_wrapper
+    }
+
+    fn build_path(&mut self) -> Option<GraphPath<'_>> {
+        let reached_t = self.timed_path.last()?.t;
+        let path = self
+            .timed_path
+            .iter()
+            .map(|timed_point| timed_point.coord.clone())
+            .collect::<Vec<_>>();
+
+        Some(self.graph_wrapper.build_path_along(path, reached_t))
+    }
+
+    fn timed_path(&self) -> Option<&[TimedPointCoord]> {
+        if self.timed_path.is_empty() {
+            None
+        } else {
+            Some(&self.timed_path)
+        }
+    }
+
+    fn reservation_table(&self) -> Option<&ReservationTable> {
+        Some(&self.reservation_table)
+    }
+
+    fn heuristic(&self, node: &PointCoord) -> u32 {
+        let graph = self.graph_wrapper.graph();
+
+        self.search
+            .goals
+            .iter()
+            .map(|goal| graph.moves(node, goal))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        self.graph_wrapper.graph().distance_name()
+    }
+
+    // A cell waits in the frontier for every timestep it is reached at
+    fn frontier(&self) -> Vec<FrontierNode<PointCoord>> {
+        // Popped from the greatest one
+        let mut entries = self.search.priority_queue.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+
+        entries
+            .into_iter()
+            .map(|(Reverse(f), Reverse(t), timed_point)| FrontierNode {
+                node: timed_point.coord.clone(),
+                g: *t,
+                h: f - t,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,258 @@
+use std::{cmp::Reverse, collections::BinaryHeap, sync::Arc};
+
+use crate::graph::{Graph, GraphWrapper, Point, PointCoord, SearchEvent, TimedPointCoord};
+
+use super::{distance_to_end, is_live, FrontierNode, PathFinder};
+
+// A* over the earliest arrival time at every cell following the graph's `Schedule`.
+// Waiting in a cell is allowed, so it's exact for FIFO costs (leaving later never
+// arrives earlier) with a period up to `MAX_WAIT`. Moving obstacles sweeping through
+// the waiting cell break that.
+pub struct TimeDependentAStar {
+    graph_wrapper: GraphWrapper,
+
+    // Dijkstra's algorithm without it
+    use_heuristic: bool,
+    priority_queue: BinaryHeap<(Reverse<u32>, Reverse<u32>, PointCoord)>,
+    timed_path: Vec<TimedPointCoord>,
+}
+
+impl TimeDependentAStar {
+    // Waiting longer than the schedule's period changes nothing, but the period of
+    // several doors may be huge. Longer waits are cut off to keep the steps quick,
+    // the found path may be late then.
+    pub const MAX_WAIT: u32 = 64;
+
+    pub fn dijkstra(graph: Arc<Graph>) -> Box<dyn PathFinder> {
+        Self::with_heuristic(graph, false)
+    }
+
+    fn with_heuristic(graph: Arc<Graph>, use_heuristic: bool) -> Box<dyn PathFinder> {
+        let graph_wrapper = GraphWrapper::new(graph);
+        let priority_queue = Self::initial_priority_queue(&graph_wrapper);
+
+        let graph = graph_wrapper.graph();
+        println!(
+            "[I] TimeDependentAStar[Heuristic: {}, period: {}]",
+            if use_heuristic {
+                graph.distance_name()
+            } else {
+                "None"
+            },
+            graph.schedule().period()
+        );
+
+        Box::new(Self {
+            graph_wrapper,
+            use_heuristic,
+            priority_queue,
+            timed_path: vec![],
+        })
+    }
+
+    fn initial_priority_queue(
+        graph_wrapper: &GraphWrapper,
+    ) -> BinaryHeap<(Reverse<u32>, Reverse<u32>, PointCoord)> {
+        BinaryHeap::from_iter(
+            graph_wrapper
+                .start_coords()
+                .iter()
+                .map(|start_coord| (Reverse(0), Reverse(0), start_coord.clone())),
+        )
+    }
+
+    // Earliest arrival onto `to` from `cur` reached at `t`, the agent waits in `cur` if it helps
+    // and stays there until it arrives
+    fn earliest_arrival(
+        graph: &Graph,
+        cur: &PointCoord,
+        t: u32,
+        to: &PointCoord,
+        to_point: &Point,
+    ) -> Option<u32> {
+        let schedule = graph.schedule();
+        let max_wait = schedule.period().min(Self::MAX_WAIT);
+
+        (t..=t + max_wait)
+            .take_while(|&departure| schedule.is_free_at(cur, departure))
+            .filter_map(|departure| {
+                let arrival = departure + graph.move_length_at(cur, to, to_point, departure)?;
+
+                let is_free = (departure..arrival).all(|t| schedule.is_free_at(cur, t))
+                    && schedule.is_free_at(to, arrival)
+                    && schedule.can_move(cur, to, arrival);
+
+                is_free.then_some(arrival)
+            })
+            .min()
+    }
+
+    fn build_timed_path(&self, end_coord: &PointCoord) -> Vec<TimedPointCoord> {
+        let mut timed_path = vec![];
+        let mut cur = end_coord.clone();
+
+        loop {
+            let (prev, t) = self.graph_wrapper.came_from(&cur).unwrap();
+            timed_path.push(TimedPointCoord {
+                coord: cur.clone(),
+                t,
+            });
+
+            if self.graph_wrapper.is_start(&cur) {
+                break;
+            }
+            cur = prev;
+        }
+        timed_path.reverse();
+
+        timed_path
+    }
+}
+
+impl PathFinder for TimeDependentAStar {
+    fn new(graph: Arc<Graph>) -> Box<dyn PathFinder>
+    where
+        Self: Sized,
+    {
+        Self::with_heuristic(graph, true)
+    }
+
+    fn name(&self) -> &'static str {
+        if self.use_heuristic {
+            "TimeDependentAStar"
+        } else {
+            "TimeDependentDijkstra"
+        }
+    }
+
+    fn step(&mut self) {
+        let mut reached = None;
+
+        while let Some((Reverse(priority), Reverse(t), cur)) = self.priority_queue.pop() {
+            // Skip `worse` points
+            if let Some((_, t_best)) = self.graph_wrapper.came_from(&cur) {
+                if t_best < t {
+                    self.graph_wrapper
+                        .emit(SearchEvent::SkippedStale { node: cur });
+                    continue;
+                }
+            }
+            self.graph_wrapper.emit(SearchEvent::Popped {
+                node: cur.clone(),
+                priority,
+            });
+
+            // Found the nearest `end`
+            if self.graph_wrapper.is_end(&cur) {
+                reached = Some(cur);
+                break;
+            }
+
+            // Mark current as seen, not mark start
+            if !self.graph_wrapper.is_start(&cur) {
+                self.graph_wrapper.seen_for_point(&cur);
+            }
+
+            let graph = self.graph_wrapper.graph();
+            let reached_points = graph
+                .neighbors(&cur)
+                .filter_map(|(to_point, to)| {
+                    let t_to = Self::earliest_arrival(graph, &cur, t, &to, &to_point)?;
+
+                    match self.graph_wrapper.came_from(&to) {
+                        Some((_, t_best)) if t_best <= t_to => None,
+                        _ => Some((to, t_to)),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            for (to, t_to) in reached_points.into_iter() {
+                let heuristic_length_end = if self.use_heuristic {
+                    distance_to_end(&self.graph_wrapper, &to)
+                } else {
+                    0
+                };
+
+                let priority = t_to + heuristic_length_end;
+
+                self.priority_queue
+                    .push((Reverse(priority), Reverse(t_to), to.clone()));
+
+                self.graph_wrapper.set_came_from(&to, &cur, t_to);
+                self.graph_wrapper.emit(SearchEvent::Relaxed {
+                    from: cur.clone(),
+                    to: to.clone(),
+                    length: t_to,
+                });
+                self.graph_wrapper
+                    .emit(SearchEvent::Pushed { node: to, priority });
+            }
+
+            return;
+        }
+
+        match reached {
+            Some(end_coord) => {
+                self.timed_path = self.build_timed_path(&end_coord);
+                self.graph_wrapper.reach_end(end_coord);
+            }
+            // Nothing left to expand, `end` can't be reached
+            None => self.graph_wrapper.completed = true,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.graph_wrapper.reset();
+
+        self.priority_queue = Self::initial_priority_queue(&self.graph_wrapper);
+        self.timed_path = vec![];
+    }
+
+    fn graph_wrapper(&self) -> &GraphWrapper {
+        &self.graph_wrapper
+    }
+
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper {
+        &mut self.graph_wrapper
+    }
+
+    fn timed_path(&self) -> Option<&[TimedPointCoord]> {
+        if self.timed_path.is_empty() {
+            None
+        } else {
+            Some(&self.timed_path)
+        }
+    }
+
+    fn heuristic(&self, node: &PointCoord) -> u32 {
+        if self.use_heuristic {
+            distance_to_end(&self.graph_wrapper, node)
+        } else {
+            0
+        }
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        if self.use_heuristic {
+            self.graph_wrapper.graph().distance_name()
+        } else {
+            "None"
+        }
+    }
+
+    fn frontier(&self) -> Vec<FrontierNode<PointCoord>> {
+        // Popped from the greatest one
+        let mut entries = self.priority_queue.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+
+        entries
+            .into_iter()
+            .filter(|(_, Reverse(t), node)| is_live(&self.graph_wrapper, node, *t))
+            .map(|(Reverse(f), Reverse(t), node)| FrontierNode {
+                node: node.clone(),
+                g: *t,
+                h: f - t,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,412 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::graph::{Graph, GraphPath, GraphWrapper, Mark, PointCoord};
+
+use super::{Dijkstra, PathFinder};
+
+// Visits every waypoint on the way from `start` to `end`. Every step finds the shortest
+// path between a pair of stops, the last one solves the visiting order.
+pub struct Waypoints {
+    graph_wrapper: GraphWrapper,
+
+    // `start`s, every waypoint and `end`s, a leg may begin at any `start` of the first stop
+    // and finish at any `end` of the last one
+    stops: Vec<Vec<PointCoord>>,
+    legs: HashMap<(usize, usize), (Vec<PointCoord>, u32)>,
+    pending_legs: Vec<(usize, usize)>,
+
+    // Visiting order of `stops`
+    order: Vec<usize>,
+    ordered_waypoints: Vec<PointCoord>,
+}
+
+impl Waypoints {
+    // Held-Karp is exponential in the number of waypoints, 2-opt is used beyond it
+    pub const EXACT_ORDER_MAX_WAYPOINTS: usize = 8;
+
+    fn stops(graph_wrapper: &GraphWrapper) -> Vec<Vec<PointCoord>> {
+        let mut stops = vec![graph_wrapper.start_coords().to_vec()];
+        stops.extend(
+            graph_wrapper
+                .graph()
+                .waypoints()
+                .into_iter()
+                .map(|waypoint| vec![waypoint]),
+        );
+        stops.push(graph_wrapper.end_coords().to_vec());
+
+        stops
+    }
+
+    // Every ordered pair, moves cost the difficulty of the entered point so legs are directed
+    fn pending_legs(stops_cnt: usize) -> Vec<(usize, usize)> {
+        let end = stops_cnt - 1;
+
+        (0..end)
+            .flat_map(|from| (1..=end).map(move |to| (from, to)))
+            .filter(|(from, to)| from != to)
+            .rev()
+            .collect()
+    }
+
+    fn leg_length(&self, from: usize, to: usize) -> Option<u32> {
+        self.legs.get(&(from, to)).map(|(_, length)| *length)
+    }
+
+    fn order_length(&self, order: &[usize]) -> Option<u32> {
+        order
+            .windows(2)
+            .map(|leg| self.leg_length(leg[0], leg[1]))
+            .sum()
+    }
+
+    // Held-Karp dynamic programming over subsets of visited waypoints
+    fn exact_order(&self) -> Option<Vec<usize>> {
+        let waypoints_cnt = self.stops.len() - 2;
+        let end = self.stops.len() - 1;
+
+        if waypoints_cnt == 0 {
+            return Some(vec![0, end]);
+        }
+
+        // `best[mask][i]` - length of visiting `mask` waypoints from `start` ending at `i`,
+        // with the previous waypoint
+        let mut best = vec![vec![None::<(u32, usize)>; waypoints_cnt]; 1 << waypoints_cnt];
+        for i in 0..waypoints_cnt {
+            best[1 << i][i] = self.leg_length(0, i + 1).map(|length| (length, usize::MAX));
+        }
+
+        for mask in 1..1usize << waypoints_cnt {
+            for i in (0..waypoints_cnt).filter(|i| mask & (1 << i) != 0) {
+                let Some((length, _)) = best[mask][i] else {
+                    continue;
+                };
+
+                for j in (0..waypoints_cnt).filter(|j| mask & (1 << j) == 0) {
+                    let Some(leg_length) = self.leg_length(i + 1, j + 1) else {
+                        continue;
+                    };
+
+                    let to = &mut best[mask | (1 << j)][j];
+                    if to.is_none_or(|(length_best, _)| length + leg_length < length_best) {
+                        *to = Some((length + leg_length, i));
+                    }
+                }
+            }
+        }
+
+        let full = (1 << waypoints_cnt) - 1;
+        let (_, mut last) = (0..waypoints_cnt)
+            .filter_map(|i| {
+                let (length, _) = best[full][i]?;
+                Some((length + self.leg_length(i + 1, end)?, i))
+            })
+            .min()?;
+
+        let mut order = vec![end];
+        let mut mask = full;
+        while last != usize::MAX {
+            order.push(last + 1);
+
+            let (_, prev) = best[mask][last].unwrap();
+            mask ^= 1 << last;
+            last = prev;
+        }
+        order.push(0);
+        order.reverse();
+
+        Some(order)
+    }
+
+    // Nearest neighbour order improved by 2-opt segment reversals
+    fn two_opt_order(&self) -> Option<Vec<usize>> {
+        let end = self.stops.len() - 1;
+
+        let mut order = vec![0];
+        let mut left = (1..end).collect::<Vec<_>>();
+        while !left.is_empty() {
+            let cur = *order.last().unwrap();
+            let nearest = (0..left.len())
+                .min_by_key(|&i| self.leg_length(cur, left[i]).unwrap_or(u32::MAX))
+                .unwrap();
+
+            order.push(left.remove(nearest));
+        }
+        order.push(end);
+
+        let order_length = |order: &[usize]| self.order_length(order).unwrap_or(u32::MAX);
+
+        let mut improved = true;
+        while improved {
+            improved = false;
+
+            for i in 1..end - 1 {
+                for j in i + 1..end {
+                    let mut reversed = order.clone();
+                    reversed[i..=j].reverse();
+
+                    if order_length(&reversed) < order_length(&order) {
+                        order = reversed;
+                        improved = true;
+                    }
+                }
+            }
+        }
+
+        self.order_length(&order).map(|_| order)
+    }
+
+    fn solve_order(&mut self) {
+        let order = if self.stops.len() - 2 <= Self::EXACT_ORDER_MAX_WAYPOINTS {
+            self.exact_order()
+        } else {
+            self.two_opt_order()
+        };
+
+        if let Some(order) = order {
+            self.ordered_waypoints = order[1..order.len() - 1]
+                .iter()
+                .map(|&stop| self.stops[stop][0].clone())
+                .collect();
+            self.order = order;
+        }
+    }
+}
+
+impl PathFinder for Waypoints {
+    fn new(graph: Arc<Graph>) -> Box<dyn PathFinder>
+    where
+        Self: Sized,
+    {
+        let graph_wrapper = GraphWrapper::new(graph);
+        let stops = Self::stops(&graph_wrapper);
+        let pending_legs = Self::pending_legs(stops.len());
+
+        println!("[I] Waypoints[Dijkstra, waypoints: {}]", stops.len() - 2);
+
+        Box::new(Self {
+            graph_wrapper,
+            stops,
+            legs: HashMap::new(),
+            pending_legs,
+            order: vec![],
+            ordered_waypoints: vec![],
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "Waypoints"
+    }
+
+    fn step(&mut self) {
+        let Some((from, to)) = self.pending_legs.pop() else {
+            self.solve_order();
+            self.graph_wrapper.completed = true;
+
+            return;
+        };
+
+        let mut events = vec![];
+        let (leg, seen) = Dijkstra::shortest_path(
+            self.graph_wrapper.graph(),
+            &self.stops[from],
+            &self.stops[to],
+            |_, _| false,
+            |event| events.push(event),
+        );
+        for event in events {
+            self.graph_wrapper.emit(event);
+        }
+
+        // Mark expanded points as seen once over all the searches
+        for point_coord in seen {
+            if self.graph_wrapper.point_at(&point_coord).is_on_the_way()
+                && self.graph_wrapper.mark(&point_coord) == Mark::Unseen
+            {
+                self.graph_wrapper.seen_for_point(&point_coord);
+            }
+        }
+
+        if let Some(leg) = leg {
+            self.legs.insert((from, to), leg);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.graph_wrapper.reset();
+
+        self.stops = Self::stops(&self.graph_wrapper);
+        self.legs = HashMap::new();
+        self.pending_legs = Self::pending_legs(self.stops.len());
+        self.order = vec![];
+        self.ordered_waypoints = vec![];
+    }
+
+    fn graph_wrapper(&self) -> &GraphWrapper {
+        &self.graph_wrapper
+    }
+
+    fn graph_wrapper_mut(&mut self) -> &mut GraphWrapper {
+        &mut self.graph_wrapper
+    }
+
+    fn build_path(&mut self) -> Option<GraphPath<'_>> {
+        if self.order.is_empty() {
+            return None;
+        }
+        let length = self.order_length(&self.order)?;
+
+        // Stitch the legs, every leg starts where the previous one ended
+        let mut path = vec![];
+        for leg in self.order.windows(2) {
+            let (leg_path, _) = &self.legs[&(leg[0], leg[1])];
+            path.extend(leg_path.iter().skip(path.len().min(1)).cloned());
+        }
+
+        Some(self.graph_wrapper.build_path_along(path, length))
+    }
+
+    fn waypoints_order(&self) -> &[PointCoord] {
+        &self.ordered_waypoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::tests::{path_length, predefined_graphs};
+    use crate::graph::Point;
+
+    // Every seventh free cell of the first layer becomes a waypoint
+    fn with_waypoints(graph: &Graph, waypoints_cnt: usize) -> Arc<Graph> {
+        let mut graph = graph.clone();
+        let free_coords = (0..graph.n())
+            .flat_map(|y| (0..graph.m()).map(move |x| PointCoord { x, y, layer: 0 }))
+            .filter(|point_coord| graph.point_at(point_coord) == Point::Free)
+            .step_by(7)
+            .take(waypoints_cnt)
+            .collect::<Vec<_>>();
+        for point_coord in &free_coords {
+            graph.set_point(point_coord, Point::Waypoint);
+        }
+
+        Arc::new(graph)
+    }
+
+    // Every leg found, the order is not solved yet
+    fn with_legs(graph: Arc<Graph>) -> Waypoints {
+        let graph_wrapper = GraphWrapper::new(graph);
+        let stops = Waypoints::stops(&graph_wrapper);
+        let mut waypoints = Waypoints {
+            graph_wrapper,
+            pending_legs: Waypoints::pending_legs(stops.len()),
+            stops,
+            legs: HashMap::new(),
+            order: vec![],
+            ordered_waypoints: vec![],
+        };
+        while !waypoints.pending_legs.is_empty() {
+            waypoints.step();
+        }
+
+        waypoints
+    }
+
+    // Trying every order of the waypoints left
+    fn shortest_order_length(waypoints: &Waypoints, order: &mut Vec<usize>) -> Option<u32> {
+        let end = waypoints.stops.len() - 1;
+        if order.len() == end {
+            order.push(end);
+            let length = waypoints.order_length(order);
+            order.pop();
+
+            return length;
+        }
+
+        (1..end)
+            .filter_map(|stop| {
+                if order.contains(&stop) {
+                    return None;
+                }
+
+                order.push(stop);
+                let length = shortest_order_length(waypoints, order);
+                order.pop();
+
+                length
+            })
+            .min()
+    }
+
+    fn is_order_of_every_stop(waypoints: &Waypoints, order: &[usize]) -> bool {
+        let mut sorted = order.to_vec();
+        sorted.sort();
+
+        order[0] == 0 && sorted == (0..waypoints.stops.len()).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn finds_shortest_path_without_waypoints() {
+        for graph in predefined_graphs() {
+            if !graph.waypoints().is_empty() {
+                continue;
+            }
+
+            let dijkstra_length = path_length(Dijkstra::new(graph.clone()).as_mut());
+
+            assert_eq!(path_length(Waypoints::new(graph).as_mut()), dijkstra_length);
+        }
+    }
+
+    #[test]
+    fn exact_order_is_shortest() {
+        for graph in predefined_graphs() {
+            if !graph.waypoints().is_empty() {
+                continue;
+            }
+
+            let waypoints = with_legs(with_waypoints(&graph, 5));
+            let order = waypoints.exact_order().unwrap();
+
+            assert!(is_order_of_every_stop(&waypoints, &order));
+            assert_eq!(
+                waypoints.order_length(&order),
+                shortest_order_length(&waypoints, &mut vec![0])
+            );
+        }
+    }
+
+    #[test]
+    fn two_opt_order_is_not_shorter_than_exact() {
+        for graph in predefined_graphs() {
+            if !graph.waypoints().is_empty() {
+                continue;
+            }
+
+            let waypoints = with_legs(with_waypoints(&graph, 10));
+            let exact_order = waypoints.exact_order().unwrap();
+            let two_opt_order = waypoints.two_opt_order().unwrap();
+
+            assert!(is_order_of_every_stop(&waypoints, &two_opt_order));
+            assert!(waypoints.order_length(&two_opt_order) >= waypoints.order_length(&exact_order));
+        }
+    }
+
+    #[test]
+    fn path_follows_solved_order() {
+        for graph in predefined_graphs() {
+            let graph = with_waypoints(&graph, 5);
+            let mut waypoints = Waypoints::new(graph.clone());
+            assert!(path_length(waypoints.as_mut()).is_some());
+            let path = waypoints.build_path().unwrap().points().to_vec();
+
+            // Every waypoint is on the path in the solved order
+            let mut points = path.iter();
+            assert_eq!(waypoints.waypoints_order().len(), graph.waypoints().len());
+            assert!(waypoints
+                .waypoints_order()
+                .iter()
+                .all(|waypoint| points.any(|point_coord| point_coord == waypoint)));
+        }
+    }
+}
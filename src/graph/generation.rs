@@ -2,12 +2,42 @@ use rand::Rng;
 
 use crate::graph::Point;
 
-use super::Graph;
+use super::{Door, Graph, MovingObstacle, PointCoord, Portal};
 
 pub enum Generation {
     Predefined1,
     Predefined2,
+    Predefined3,
+    Predefined4,
+    Predefined5,
+    Predefined6,
+    Predefined7,
+    Terrain,
+    Dynamic,
     Random,
+    RandomWaypoints,
+}
+
+impl std::fmt::Display for Generation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Generation::Predefined1 => "Predefined 1",
+                Generation::Predefined2 => "Predefined 2",
+                Generation::Predefined3 => "Predefined 3",
+                Generation::Predefined4 => "Predefined 4",
+                Generation::Predefined5 => "Predefined 5",
+                Generation::Predefined6 => "Predefined 6",
+                Generation::Predefined7 => "Predefined 7",
+                Generation::Terrain => "Random terrain",
+                Generation::Dynamic => "Dynamic",
+                Generation::Random => "Random",
+                Generation::RandomWaypoints => "Random waypoints",
+            }
+        )
+    }
 }
 
 impl Graph {
@@ -15,7 +45,15 @@ impl Graph {
         match generation {
             Generation::Predefined1 => Graph::generate_graph_predefined_1(),
             Generation::Predefined2 => Graph::generate_graph_predefined_2(),
+            Generation::Predefined3 => Graph::generate_graph_predefined_3(),
+            Generation::Predefined4 => Graph::generate_graph_predefined_4(),
+            Generation::Predefined5 => Graph::generate_graph_predefined_5(),
+            Generation::Predefined6 => Graph::generate_graph_predefined_6(),
+            Generation::Predefined7 => Graph::generate_graph_predefined_7(),
+            Generation::Terrain => Graph::generate_terrain(),
+            Generation::Dynamic => Graph::generate_graph_dynamic(),
             Generation::Random => Graph::generate_random(),
+            Generation::RandomWaypoints => Graph::generate_random_waypoints(),
         }
     }
 
@@ -46,7 +84,7 @@ impl Graph {
         mtx[7][5] = Point::Obstacle { length: 2 };
         mtx[7][6] = Point::Obstacle { length: 3 };
 
-        Graph { mtx }
+        Graph::new(mtx)
     }
 
     fn generate_graph_predefined_2() -> Self {
@@ -66,7 +104,355 @@ impl Graph {
             mtx[Self::N - 2][j] = Point::Obstacle { length: 3 };
         }
 
-        Graph { mtx }
+        Graph::new(mtx)
+    }
+
+    fn generate_graph_predefined_3() -> Self {
+        println!("[I] generate_graph_predefined_3");
+
+        let mut mtx = vec![vec![Point::Free; Self::M]; Self::N];
+
+        mtx[Self::N - 1][0] = Point::Start;
+        mtx[0][Self::M - 1] = Point::End;
+
+        for row in &mut mtx[2..Self::N - 2] {
+            row[5] = Point::Obstacle { length: 3 };
+        }
+        for point in &mut mtx[4][0..3] {
+            *point = Point::Obstacle { length: 2 };
+        }
+
+        mtx[1][1] = Point::Waypoint;
+        mtx[2][7] = Point::Waypoint;
+        mtx[5][3] = Point::Waypoint;
+        mtx[7][8] = Point::Waypoint;
+        mtx[8][3] = Point::Waypoint;
+
+        Graph::new(mtx)
+    }
+
+    fn generate_graph_predefined_4() -> Self {
+        println!("[I] generate_graph_predefined_4");
+
+        let mut mtx = vec![vec![Point::Free; Self::M]; Self::N];
+
+        mtx[0][0] = Point::Start;
+        mtx[Self::N - 1][2] = Point::Start;
+        mtx[0][Self::M - 1] = Point::End;
+        mtx[Self::N - 4][Self::M - 1] = Point::End;
+
+        for row in &mut mtx[0..Self::N - 3] {
+            row[5] = Point::Obstacle { length: 3 };
+        }
+        mtx[3][5] = Point::Free;
+        mtx[5][5] = Point::Obstacle { length: 0 };
+
+        for point in &mut mtx[Self::N - 3][6..] {
+            *point = Point::Obstacle { length: 2 };
+        }
+
+        Graph::new(mtx)
+    }
+
+    // Conveyor belts and thin walls
+    fn generate_graph_predefined_5() -> Self {
+        println!("[I] generate_graph_predefined_5");
+
+        let mut mtx = vec![vec![Point::Free; Self::M]; Self::N];
+
+        mtx[0][Self::M - 1] = Point::Start;
+        mtx[Self::N - 1][Self::M - 1] = Point::End;
+
+        mtx[1][2] = Point::Obstacle { length: 2 };
+        mtx[5][4] = Point::Obstacle { length: 1 };
+
+        let mut graph = Graph::new(mtx);
+
+        // Crossing the belt drags everything back to the left border
+        for j in 1..Self::M {
+            graph.set_one_way(
+                PointCoord {
+                    y: 3,
+                    x: j,
+                    layer: 0,
+                },
+                PointCoord {
+                    y: 3,
+                    x: j - 1,
+                    layer: 0,
+                },
+            );
+        }
+
+        // Faster way along the bottom to the right
+        for j in 1..Self::M - 4 {
+            graph.set_one_way(
+                PointCoord {
+                    y: 8,
+                    x: j,
+                    layer: 0,
+                },
+                PointCoord {
+                    y: 8,
+                    x: j + 1,
+                    layer: 0,
+                },
+            );
+        }
+
+        for j in 3..Self::M {
+            graph.add_wall(
+                PointCoord {
+                    y: 6,
+                    x: j,
+                    layer: 0,
+                },
+                PointCoord {
+                    y: 7,
+                    x: j,
+                    layer: 0,
+                },
+            );
+        }
+        for i in 7..Self::N - 1 {
+            graph.add_wall(
+                PointCoord {
+                    y: i,
+                    x: 6,
+                    layer: 0,
+                },
+                PointCoord {
+                    y: i,
+                    x: 7,
+                    layer: 0,
+                },
+            );
+        }
+        graph.add_wall(
+            PointCoord {
+                y: Self::N - 2,
+                x: Self::M - 1,
+                layer: 0,
+            },
+            PointCoord {
+                y: Self::N - 1,
+                x: Self::M - 1,
+                layer: 0,
+            },
+        );
+
+        graph
+    }
+
+    // Portals through a wall of hard obstacles
+    fn generate_graph_predefined_6() -> Self {
+        println!("[I] generate_graph_predefined_6");
+
+        let mut mtx = vec![vec![Point::Free; Self::M]; Self::N];
+
+        mtx[0][0] = Point::Start;
+        mtx[Self::N - 1][Self::M - 1] = Point::End;
+
+        for row in mtx.iter_mut() {
+            row[5] = Point::Obstacle { length: 3 };
+        }
+
+        let mut graph = Graph::new(mtx);
+
+        graph.add_portal(Portal {
+            a: PointCoord {
+                y: 8,
+                x: 1,
+                layer: 0,
+            },
+            b: PointCoord {
+                y: 1,
+                x: 8,
+                layer: 0,
+            },
+            length: 3,
+        });
+        graph.add_portal(Portal {
+            a: PointCoord {
+                y: 2,
+                x: 3,
+                layer: 0,
+            },
+            b: PointCoord {
+                y: 6,
+                x: 7,
+                layer: 0,
+            },
+            length: 6,
+        });
+
+        graph
+    }
+
+    // Three floors connected by stairs and an elevator (portal between the floors)
+    fn generate_graph_predefined_7() -> Self {
+        println!("[I] generate_graph_predefined_7");
+
+        let mut mtx = vec![vec![vec![Point::Free; Self::M]; Self::N]; 3];
+
+        mtx[0][0][0] = Point::Start;
+        mtx[2][Self::N - 1][Self::M - 1] = Point::End;
+
+        // Ground floor stairs lead to the first floor only
+        mtx[0][1][3] = Point::Stairs;
+        mtx[1][1][3] = Point::Stairs;
+        // First floor stairs lead to the second floor only
+        mtx[1][1][8] = Point::Stairs;
+        mtx[2][1][8] = Point::Stairs;
+
+        for point in mtx[0][4].iter_mut().take(Self::M - 1) {
+            *point = Point::Obstacle { length: 3 };
+        }
+        for row in mtx[1].iter_mut().skip(2) {
+            row[5] = Point::Obstacle { length: 2 };
+        }
+        for point in mtx[2][5].iter_mut().skip(1) {
+            *point = Point::Obstacle { length: 1 };
+        }
+
+        let mut graph = Graph::new_layered(mtx);
+
+        graph.add_portal(Portal {
+            a: PointCoord {
+                y: Self::N - 1,
+                x: 0,
+                layer: 0,
+            },
+            b: PointCoord {
+                y: Self::N - 1,
+                x: 0,
+                layer: 2,
+            },
+            length: 10,
+        });
+
+        graph
+    }
+
+    // Heightmap of random hills, moving onto a cell costs 1 + its height
+    fn generate_terrain() -> Self {
+        println!("[I] generate_terrain");
+
+        let mut rng = rand::thread_rng();
+
+        // Center, radius and height of every hill
+        let hills = (0..rng.gen_range(3..=8))
+            .map(|_| {
+                (
+                    rng.gen_range(0.0..Self::N as f32),
+                    rng.gen_range(0.0..Self::M as f32),
+                    rng.gen_range(1.5..4.0),
+                    rng.gen_range(5.0..30.0),
+                )
+            })
+            .collect::<Vec<(f32, f32, f32, f32)>>();
+
+        let mut mtx = vec![vec![Point::Free; Self::M]; Self::N];
+
+        for (i, row) in mtx.iter_mut().enumerate() {
+            for (j, point) in row.iter_mut().enumerate() {
+                let height = hills
+                    .iter()
+                    .map(|&(hill_i, hill_j, radius, height)| {
+                        let distance_2 = (i as f32 - hill_i).powi(2) + (j as f32 - hill_j).powi(2);
+                        height * (-distance_2 / (radius * radius)).exp()
+                    })
+                    .sum::<f32>();
+
+                *point = Point::Terrain {
+                    cost: 1 + height.round() as u32,
+                };
+            }
+        }
+
+        mtx[0][0] = Point::Start;
+        mtx[Self::N - 1][Self::M - 1] = Point::End;
+
+        Graph::new(mtx)
+    }
+
+    // A periodic door and a patrolled gap in a wall, a road with rush hours
+    fn generate_graph_dynamic() -> Self {
+        println!("[I] generate_graph_dynamic");
+
+        let mut mtx = vec![vec![Point::Free; Self::M]; Self::N];
+
+        mtx[0][0] = Point::Start;
+        mtx[Self::N - 1][Self::M - 1] = Point::End;
+
+        for point in mtx[5].iter_mut() {
+            *point = Point::Obstacle { length: 3 };
+        }
+        mtx[5][2] = Point::Free;
+        mtx[5][7] = Point::Free;
+
+        let mut graph = Graph::new(mtx);
+        let schedule = graph.schedule_mut();
+
+        schedule.add_door(Door {
+            coord: PointCoord {
+                y: 5,
+                x: 2,
+                layer: 0,
+            },
+            period: 8,
+            open: 2,
+            phase: 0,
+        });
+
+        // Up and down along the gap
+        let patrol = [3, 4, 5, 6, 7, 6, 5, 4];
+        schedule.add_moving_obstacle(MovingObstacle {
+            trajectory: patrol
+                .into_iter()
+                .map(|y| PointCoord { y, x: 7, layer: 0 })
+                .collect(),
+        });
+
+        for j in 0..Self::M {
+            schedule.set_costs(
+                PointCoord {
+                    y: Self::N - 2,
+                    x: j,
+                    layer: 0,
+                },
+                vec![1, 1, 1, 1, 1, 1, 8, 8, 8, 8],
+            );
+        }
+
+        graph
+    }
+
+    fn generate_random_waypoints() -> Self {
+        let mut graph = Graph::generate_random();
+
+        println!("[I] generate_random_waypoints");
+
+        let mut rng = rand::thread_rng();
+
+        let waypoints_cnt = rng.gen_range(2..=12);
+        let mut waypoints_generated = 0;
+
+        while waypoints_generated < waypoints_cnt {
+            let (i, j) = (rng.gen_range(0..Self::N), rng.gen_range(0..Self::M));
+
+            let point_coord = PointCoord {
+                x: j,
+                y: i,
+                layer: 0,
+            };
+            if graph.point_at(&point_coord) == Point::Free {
+                graph.set_point(&point_coord, Point::Waypoint);
+                waypoints_generated += 1;
+            }
+        }
+
+        graph
     }
 
     fn generate_random() -> Self {
@@ -112,6 +498,6 @@ impl Graph {
             }
         }
 
-        Graph { mtx }
+        Graph::new(mtx)
     }
 }
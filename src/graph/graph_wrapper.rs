@@ -1,132 +1,448 @@
-use crate::graph::{Graph, Point, PointCoord};
+use std::sync::Arc;
 
-pub struct GraphWrapper {
-    graph: Graph,
+use crate::graph::{
+    Graph, History, Mark, Point, PointCoord, Progress, SearchEvent, SearchGraph, SearchObserver,
+    SearchOverlay,
+};
 
-    start_coord: PointCoord,
-    end_coord: PointCoord,
+type Node<G> = <G as SearchGraph>::Node;
+// Node expanded by a step and the nodes it has relaxed
+type StepNodes<G> = (Option<Node<G>>, Vec<Node<G>>);
+
+pub struct GraphWrapper<G: SearchGraph = Graph> {
+    // Never changed by the search, other searches may share it
+    graph: Arc<G>,
+
+    start_coords: Vec<Node<G>>,
+    end_coords: Vec<Node<G>>,
 
     /*hz*/ pub completed: bool,
+    // The first reached of `end_coords`
+    reached_end: Option<Node<G>>,
+    // Points of the last built path
+    path: Vec<Node<G>>,
+    path_length: u32,
 
     pub seen_points: u32,
-    pub came_from: Vec<Vec<Option<(PointCoord, u32)>>>,
+    pub pruned_points: u32,
+    overlay: SearchOverlay,
+    history: History<Node<G>>,
+    // Kept over resets, replayed steps are not emitted again
+    observers: Vec<Box<dyn SearchObserver<Node<G>>>>,
 }
 
-pub struct GraphPath<'a> {
-    from_coord: &'a PointCoord,
-    to_coord: &'a PointCoord,
+pub struct GraphPath<'a, N = PointCoord> {
+    from_coord: &'a N,
+    to_coord: &'a N,
 
     seen_points: u32,
+    pruned_points: u32,
     length: u32,
+    // Bytes taken by the graph and the search state
+    memory_usage: usize,
+
+    points: Vec<N>,
 }
 
-impl std::fmt::Display for GraphPath<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Path\n")?;
-        write!(f, "\tFrom:\t\t{}\n", self.from_coord)?;
-        write!(f, "\tTo:\t\t{}\n", self.to_coord)?;
-        write!(f, "\tSeen points:\t{}\n", self.seen_points)?;
-        write!(f, "\tLength:\t\t{}\n", self.length)
+impl<N> GraphPath<'_, N> {
+    pub fn length(&self) -> u32 {
+        self.length
     }
-}
 
-impl GraphWrapper {
-    pub fn new(graph: Graph) -> Self {
-        let start_coord = graph.start().expect("No start point");
-        let end_coord = graph.end().expect("No end point");
+    // Every point of the path from `start` to `end` inclusive
+    pub fn points(&self) -> &[N] {
+        &self.points
+    }
+}
 
-        let n = graph.n();
-        let m = graph.m();
+impl<N: std::fmt::Display> std::fmt::Display for GraphPath<'_, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Path")?;
+        writeln!(f, "\tFrom:\t\t{}", self.from_coord)?;
+        writeln!(f, "\tTo:\t\t{}", self.to_coord)?;
+        writeln!(f, "\tSeen points:\t{}", self.seen_points)?;
+        if self.pruned_points > 0 {
+            writeln!(f, "\tPruned points:\t{}", self.pruned_points)?;
+        }
+        writeln!(f, "\tLength:\t\t{}", self.length)?;
+        writeln!(f, "\tMemory:\t\t{} KiB", self.memory_usage.div_ceil(1024))
+    }
+}
 
-        let mut came_from = vec![vec![None; m]; n];
-        came_from[start_coord.y][start_coord.x] = Some((start_coord.clone(), 0));
+impl<G: SearchGraph> GraphWrapper<G> {
+    pub fn new(graph: Arc<G>) -> Self {
+        let start_coords = graph.starts();
+        let end_coords = graph.ends();
+        if start_coords.is_empty() {
+            println!("[E] No start point");
+        }
+        if end_coords.is_empty() {
+            println!("[E] No end point");
+        }
 
-        GraphWrapper {
+        let mut graph_wrapper = GraphWrapper {
+            overlay: SearchOverlay::new(graph.node_count()),
             graph,
-            start_coord,
-            end_coord,
-            came_from,
+            start_coords,
+            end_coords,
             seen_points: 0,
+            pruned_points: 0,
             completed: false,
+            reached_end: None,
+            path: vec![],
+            path_length: 0,
+            history: History::new(),
+            observers: vec![],
+        };
+        graph_wrapper.completed = graph_wrapper.is_unsearchable();
+        graph_wrapper.init_came_from();
+
+        graph_wrapper
+    }
+
+    // Without a start or an end (e.g. removed in the editor) the search is completed at once
+    // and the path is not found
+    fn is_unsearchable(&self) -> bool {
+        self.start_coords.is_empty() || self.end_coords.is_empty()
+    }
+
+    fn init_came_from(&mut self) {
+        for start_coord in self.start_coords.clone() {
+            self.set_came_from(&start_coord, &start_coord, 0);
+        }
+    }
+
+    // Parent of the node and the length of the best known path to it
+    pub fn came_from(&self, point_coord: &Node<G>) -> Option<(Node<G>, u32)> {
+        let (parent, length) = self.overlay.came_from(self.graph.node_index(point_coord))?;
+
+        Some((self.graph.node_at(parent as usize), length))
+    }
+
+    pub fn set_came_from(&mut self, point_coord: &Node<G>, parent: &Node<G>, length: u32) {
+        let parent = self.graph.node_index(parent) as u32;
+
+        self.write_came_from(point_coord, Some((parent, length)));
+    }
+
+    pub fn remove_came_from(&mut self, point_coord: &Node<G>) -> Option<(Node<G>, u32)> {
+        let came_from = self.came_from(point_coord);
+        self.write_came_from(point_coord, None);
+
+        came_from
+    }
+
+    fn write_came_from(&mut self, point_coord: &Node<G>, came_from: Option<(u32, u32)>) {
+        let index = self.graph.node_index(point_coord);
+
+        self.history
+            .record_came_from(index, self.overlay.came_from(index), came_from);
+        self.overlay.set_came_from(index, came_from);
+    }
+
+    pub fn is_reached(&self, point_coord: &Node<G>) -> bool {
+        self.overlay
+            .came_from(self.graph.node_index(point_coord))
+            .is_some()
+    }
+
+    pub fn mark(&self, point_coord: &Node<G>) -> Mark {
+        self.overlay.mark(self.graph.node_index(point_coord))
+    }
+
+    fn set_mark(&mut self, point_coord: &Node<G>, mark: Mark) {
+        let index = self.graph.node_index(point_coord);
+
+        self.history
+            .record_mark(index, self.overlay.mark(index), mark);
+        self.overlay.set_mark(index, mark);
+    }
+
+    // Marks a seen node as lying on the found path
+    fn mark_path(&mut self, point_coord: &Node<G>) {
+        if self.mark(point_coord) == Mark::Seen {
+            self.set_mark(point_coord, Mark::Path);
         }
     }
 
+    // Outgoing edges to the nodes not seen yet, seen ones are not entered again
+    pub fn edges(&self, point_coord: &Node<G>) -> Vec<(Node<G>, u32)> {
+        self.graph
+            .edges(point_coord)
+            .into_iter()
+            .filter(|(to, _)| self.mark(to) == Mark::Unseen)
+            .collect()
+    }
+
+    // Bytes taken by the graph and the search state
+    pub fn memory_usage(&self) -> usize {
+        self.graph.memory_usage() + self.overlay.memory_usage()
+    }
+
     pub fn reset(&mut self) {
-        self.graph.reset();
-        self.completed = false;
+        self.completed = self.is_unsearchable();
+        self.reached_end = None;
+        self.path = vec![];
+        self.path_length = 0;
         self.seen_points = 0;
+        self.pruned_points = 0;
+        self.overlay.clear();
+        self.history = History::new();
+        self.init_came_from();
+
+        self.emit(SearchEvent::Reset);
+    }
+
+    fn progress(&self) -> Progress<Node<G>> {
+        Progress {
+            seen_points: self.seen_points,
+            pruned_points: self.pruned_points,
+            completed: self.completed,
+            reached_end: self.reached_end.clone(),
+            path: self.path.clone(),
+            path_length: self.path_length,
+        }
+    }
+
+    fn restore(&mut self, progress: Progress<Node<G>>) {
+        self.seen_points = progress.seen_points;
+        self.pruned_points = progress.pruned_points;
+        self.completed = progress.completed;
+        self.reached_end = progress.reached_end;
+        self.path = progress.path;
+        self.path_length = progress.path_length;
+    }
+
+    // Records the changes of the search from now on as the next step
+    pub fn begin_step(&mut self) {
+        let progress = self.progress();
+
+        self.history.begin_step(progress);
+    }
 
-        let n = self.graph.n();
-        let m = self.graph.m();
-        self.came_from = vec![vec![None; m]; n];
-        self.came_from[self.start_coord.y][self.start_coord.x] =
-            Some((self.start_coord.clone(), 0));
+    // Reverts the last applied step, `false` at the beginning
+    pub fn step_back(&mut self) -> bool {
+        let progress = self.progress();
+
+        match self.history.undo(&mut self.overlay, progress) {
+            Some(progress) => {
+                self.restore(progress);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Applies the next recorded step again, `false` if there is none
+    pub fn replay_step(&mut self) -> bool {
+        match self.history.redo(&mut self.overlay) {
+            Some(progress) => {
+                self.restore(progress);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Steps back or replays until `step` steps are applied, as far as recorded
+    pub fn jump_to(&mut self, step: usize) {
+        while self.history.cursor() > step && self.step_back() {}
+        while self.history.cursor() < step && self.replay_step() {}
+    }
+
+    // Number of the steps applied and of the recorded ones
+    pub fn timeline(&self) -> (usize, usize) {
+        (self.history.cursor(), self.history.len())
+    }
+
+    // The search went on further than the shown state
+    pub fn is_rewound(&self) -> bool {
+        self.history.is_rewound()
+    }
+
+    // The last applied step made no progress, e.g. of a search which can't go on
+    pub fn is_last_step_idle(&self) -> bool {
+        self.history
+            .cursor()
+            .checked_sub(1)
+            .is_some_and(|step| self.history.is_idle(step))
+    }
+
+    // Nodes of the last applied step
+    pub fn last_step(&self) -> Option<StepNodes<G>> {
+        let step = self.history.cursor().checked_sub(1)?;
+        let (expanded, relaxed) = self.history.expanded_and_relaxed(step);
+
+        Some((
+            expanded.map(|index| self.graph.node_at(index)),
+            relaxed
+                .into_iter()
+                .map(|index| self.graph.node_at(index))
+                .collect(),
+        ))
+    }
+
+    pub fn subscribe(&mut self, observer: Box<dyn SearchObserver<Node<G>>>) {
+        self.observers.push(observer);
+    }
+
+    // Starts over on another graph, the observers stay subscribed
+    pub fn rebuild(&mut self, graph: Arc<G>) {
+        let observers = std::mem::take(&mut self.observers);
+
+        *self = GraphWrapper::new(graph);
+        self.observers = observers;
+    }
+
+    pub fn emit(&mut self, event: SearchEvent<Node<G>>) {
+        self.history.record_event();
+
+        for observer in &mut self.observers {
+            observer.on_event(&event);
+        }
+    }
+
+    // Steps which have reached the node first and expanded it, reached before
+    // the first step (e.g. a start) is step 0
+    pub fn discovered_and_expanded(&self, point_coord: &Node<G>) -> (Option<usize>, Option<usize>) {
+        let (discovered, expanded) = self
+            .history
+            .discovered_and_expanded(self.graph.node_index(point_coord));
+
+        (
+            discovered.or_else(|| self.is_reached(point_coord).then_some(0)),
+            expanded,
+        )
+    }
+
+    pub fn reach_end(&mut self, end_coord: Node<G>) {
+        if let Some((_, length)) = self.came_from(&end_coord) {
+            self.emit(SearchEvent::GoalReached {
+                node: end_coord.clone(),
+                length,
+            });
+        }
+
+        self.reached_end = Some(end_coord);
+        self.completed = true;
     }
 
     // TODO: Do not build path if it is already built
-    pub fn build_path<'a>(&'a mut self) -> GraphPath<'a> {
-        let mut cur = self.end_coord.clone();
+    pub fn build_path(&mut self) -> Option<GraphPath<'_, Node<G>>> {
+        let mut cur = self.reached_end.clone()?;
+        let mut points = vec![cur.clone()];
 
-        let (_, length) = &self.came_from[cur.y][cur.x]
-            .clone()
+        let (_, length) = self
+            .came_from(&cur)
             .expect("Something went wrong. End point is not reached?");
 
-        while let Some((cur_, _length)) = &self.came_from[cur.y][cur.x] {
-            if &self.start_coord == cur_ {
+        while let Some((cur_, _length)) = self.came_from(&cur) {
+            points.push(cur_.clone());
+
+            if self.is_start(&cur_) {
                 break;
             }
 
-            // So ok, we can modify graph's mtx directlly cus the same module
-            self.graph.mtx[cur_.y][cur_.x] = Point::Path {
-                initial_point: Box::new(match self.graph.point_at(&cur_) {
-                    &Point::Seen { ref initial_point } => *initial_point.clone(),
-                    _ => unreachable!(),
-                }),
-            };
+            self.mark_path(&cur_);
 
-            cur = cur_.clone();
+            cur = cur_;
         }
+        points.reverse();
+        self.path = points.clone();
+        self.path_length = length;
+
+        Some(self.path_along(points, length))
+    }
+
+    // Marks an already known path, e.g. one found in a space-time search
+    pub fn build_path_along(&mut self, path: Vec<Node<G>>, length: u32) -> GraphPath<'_, Node<G>> {
+        for point_coord in &path {
+            self.mark_path(point_coord);
+        }
+        self.path = path.clone();
+        self.path_length = length;
+
+        self.path_along(path, length)
+    }
+
+    // Path from one of `start_coords` to one of `end_coords` through `points`
+    // without marking it
+    pub fn path_along(&self, points: Vec<Node<G>>, length: u32) -> GraphPath<'_, Node<G>> {
+        let from_coord = self
+            .start_coords
+            .iter()
+            .find(|start_coord| points.first() == Some(start_coord))
+            .expect("Path doesn't begin at a start point");
+        let to_coord = self
+            .end_coords
+            .iter()
+            .find(|end_coord| points.last() == Some(end_coord))
+            .expect("Path doesn't end at an end point");
 
         GraphPath {
-            from_coord: &self.start_coord,
-            to_coord: &self.end_coord,
+            from_coord,
+            to_coord,
 
             seen_points: self.seen_points,
-            length: *length,
+            pruned_points: self.pruned_points,
+            length,
+            memory_usage: self.memory_usage(),
+            points,
         }
     }
 
-    pub fn seen_for_point(&mut self, point_coord: &PointCoord) {
+    pub fn seen_for_point(&mut self, point_coord: &Node<G>) {
         self.seen_points += 1;
 
-        let point = self.point_at_mut(point_coord);
+        self.set_mark(point_coord, Mark::Seen);
+    }
 
-        *point = Point::Seen {
-            initial_point: Box::new(point.clone()),
-        };
+    pub fn pruned_for_point(&mut self, point_coord: &Node<G>) {
+        self.pruned_points += 1;
+
+        self.set_mark(point_coord, Mark::Pruned);
     }
 
-    pub fn point_at<'a>(&'a self, point_coord: &PointCoord) -> &'a Point {
-        self.graph.point_at(point_coord)
+    pub fn path(&self) -> &[Node<G>] {
+        &self.path
+    }
+
+    // Length of the last built path, `None` until one is built
+    pub fn path_length(&self) -> Option<u32> {
+        (!self.path.is_empty()).then_some(self.path_length)
+    }
+
+    pub fn start_coords(&self) -> &[Node<G>] {
+        &self.start_coords
+    }
+
+    pub fn end_coords(&self) -> &[Node<G>] {
+        &self.end_coords
     }
 
-    pub fn start_coord<'a>(&'a self) -> &'a PointCoord {
-        &self.start_coord
+    pub fn is_start(&self, point_coord: &Node<G>) -> bool {
+        self.start_coords.contains(point_coord)
     }
 
-    pub fn end_coord<'a>(&'a self) -> &'a PointCoord {
-        &self.end_coord
+    pub fn is_end(&self, point_coord: &Node<G>) -> bool {
+        self.end_coords.contains(point_coord)
     }
 
-    pub fn graph<'a>(&'a self) -> &'a Graph {
+    pub fn graph(&self) -> &G {
         &self.graph
     }
 
+    pub fn shared_graph(&self) -> Arc<G> {
+        Arc::clone(&self.graph)
+    }
+
     pub fn is_completed(&self) -> bool {
         self.completed
     }
+}
 
-    fn point_at_mut<'a>(&'a mut self, point_coord: &PointCoord) -> &'a mut Point {
-        self.graph.point_at_mut(point_coord)
+impl GraphWrapper<Graph> {
+    pub fn point_at(&self, point_coord: &PointCoord) -> Point {
+        self.graph.point_at(point_coord)
     }
 }
@@ -0,0 +1,216 @@
+use super::{Mark, SearchOverlay};
+
+// Change of one node of a `SearchOverlay`, by its index
+#[derive(Clone)]
+enum Change {
+    Mark {
+        index: usize,
+        from: Mark,
+        to: Mark,
+    },
+    CameFrom {
+        index: usize,
+        from: Option<(u32, u32)>,
+        to: Option<(u32, u32)>,
+    },
+}
+
+// Counters and results of a search, restored as a whole
+#[derive(Clone)]
+pub struct Progress<N> {
+    pub seen_points: u32,
+    pub pruned_points: u32,
+    pub completed: bool,
+    pub reached_end: Option<N>,
+    pub path: Vec<N>,
+    pub path_length: u32,
+}
+
+#[derive(Clone)]
+struct StepRecord<N> {
+    changes: Vec<Change>,
+    // Search events emitted by the step
+    events: usize,
+    before: Progress<N>,
+    after: Progress<N>,
+}
+
+// Changes made by every step of a search, so it can be stepped back and replayed
+#[derive(Clone)]
+pub struct History<N> {
+    steps: Vec<StepRecord<N>>,
+    // Number of the steps applied to the overlay, less than `steps.len()` once stepped back
+    cursor: usize,
+}
+
+impl<N: Clone> History<N> {
+    pub fn new() -> Self {
+        History {
+            steps: vec![],
+            cursor: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_rewound(&self) -> bool {
+        self.cursor < self.steps.len()
+    }
+
+    // Starts recording a new step at the end of the history
+    pub fn begin_step(&mut self, progress: Progress<N>) {
+        assert!(!self.is_rewound(), "New step in the middle of the history");
+
+        self.seal(progress.clone());
+        self.steps.push(StepRecord {
+            changes: vec![],
+            events: 0,
+            before: progress.clone(),
+            after: progress,
+        });
+        self.cursor += 1;
+    }
+
+    // Progress of the last step as it is now, the search may change it after the step too
+    fn seal(&mut self, progress: Progress<N>) {
+        if self.is_rewound() {
+            return;
+        }
+
+        if let Some(step) = self.steps.last_mut() {
+            step.after = progress;
+        }
+    }
+
+    pub fn record_mark(&mut self, index: usize, from: Mark, to: Mark) {
+        if from != to {
+            self.record(Change::Mark { index, from, to });
+        }
+    }
+
+    pub fn record_came_from(
+        &mut self,
+        index: usize,
+        from: Option<(u32, u32)>,
+        to: Option<(u32, u32)>,
+    ) {
+        if from != to {
+            self.record(Change::CameFrom { index, from, to });
+        }
+    }
+
+    pub fn record_event(&mut self) {
+        if self.is_rewound() {
+            return;
+        }
+
+        if let Some(step) = self.steps.last_mut() {
+            step.events += 1;
+        }
+    }
+
+    // Changes before the first step (e.g. of starts) are the initial state
+    fn record(&mut self, change: Change) {
+        if self.is_rewound() {
+            return;
+        }
+
+        if let Some(step) = self.steps.last_mut() {
+            step.changes.push(change);
+        }
+    }
+
+    // Reverts the last applied step, `None` at the beginning
+    pub fn undo(
+        &mut self,
+        overlay: &mut SearchOverlay,
+        progress: Progress<N>,
+    ) -> Option<Progress<N>> {
+        self.seal(progress);
+        let step = self.steps[..self.cursor].last()?;
+
+        for change in step.changes.iter().rev() {
+            match *change {
+                Change::Mark { index, from, .. } => overlay.set_mark(index, from),
+                Change::CameFrom { index, from, .. } => overlay.set_came_from(index, from),
+            }
+        }
+        self.cursor -= 1;
+
+        Some(step.before.clone())
+    }
+
+    // Applies the next recorded step again, `None` at the end of the history
+    pub fn redo(&mut self, overlay: &mut SearchOverlay) -> Option<Progress<N>> {
+        let step = self.steps.get(self.cursor)?;
+
+        for change in &step.changes {
+            match *change {
+                Change::Mark { index, to, .. } => overlay.set_mark(index, to),
+                Change::CameFrom { index, to, .. } => overlay.set_came_from(index, to),
+            }
+        }
+        self.cursor += 1;
+
+        Some(step.after.clone())
+    }
+
+    // The step neither changed a node nor emitted an event
+    pub fn is_idle(&self, step: usize) -> bool {
+        let step = &self.steps[step];
+
+        step.changes.is_empty() && step.events == 0
+    }
+
+    // Index of the node the step has marked seen (expanded) and of the nodes
+    // it has reached or reached by a shorter path
+    pub fn expanded_and_relaxed(&self, step: usize) -> (Option<usize>, Vec<usize>) {
+        let changes = &self.steps[step].changes;
+
+        let expanded = changes.iter().find_map(|change| match *change {
+            Change::Mark {
+                index,
+                to: Mark::Seen,
+                ..
+            } => Some(index),
+            _ => None,
+        });
+        let relaxed = changes
+            .iter()
+            .filter_map(|change| match *change {
+                Change::CameFrom {
+                    index, to: Some(_), ..
+                } => Some(index),
+                _ => None,
+            })
+            .collect();
+
+        (expanded, relaxed)
+    }
+
+    // Numbers (from 1) of the applied steps which have reached the node first
+    // and marked it seen (expanded)
+    pub fn discovered_and_expanded(&self, index: usize) -> (Option<usize>, Option<usize>) {
+        let step_with = |is_change: &dyn Fn(&Change) -> bool| {
+            self.steps[..self.cursor]
+                .iter()
+                .position(|step| step.changes.iter().any(is_change))
+                .map(|step| step + 1)
+        };
+
+        (
+            step_with(
+                &|change| matches!(change, Change::CameFrom { index: changed, to: Some(_), .. } if *changed == index),
+            ),
+            step_with(
+                &|change| matches!(change, Change::Mark { index: changed, to: Mark::Seen, .. } if *changed == index),
+            ),
+        )
+    }
+}
@@ -0,0 +1,321 @@
+use std::{fmt::Write, str::SplitWhitespace};
+
+use anyhow::{bail, Context};
+
+use super::{Door, Graph, HexOrientation, MovingObstacle, Point, PointCoord, Portal, Topology};
+
+// Text form of a graph keeping all of it: a header, the cells as `Display` draws them,
+// then a line for every terrain cost, one-way cell, wall, portal and scheduled change.
+// Cells are written `y x layer`.
+impl Graph {
+    pub fn to_map_file(&self) -> String {
+        let mut map = String::new();
+
+        writeln!(map, "size {} {} {}", self.layers, self.n, self.m).unwrap();
+        writeln!(map, "topology {}", topology_name(self.topology)).unwrap();
+        writeln!(map, "wrap {}", self.wrap).unwrap();
+        writeln!(map, "stairs_length {}", self.stairs_length).unwrap();
+        writeln!(map).unwrap();
+        writeln!(map, "{self}").unwrap();
+
+        for (index, point) in self.points.iter().enumerate() {
+            if let Point::Terrain { cost } = point {
+                writeln!(map, "terrain {} {cost}", coord(&self.coord_of(index))).unwrap();
+            }
+        }
+
+        let mut one_way = self.one_way.iter().collect::<Vec<_>>();
+        one_way.sort();
+        for (point_coord, exit) in one_way {
+            writeln!(map, "one_way {} {}", coord(point_coord), coord(exit)).unwrap();
+        }
+
+        let mut walls = self.walls.iter().collect::<Vec<_>>();
+        walls.sort();
+        for (a, b) in walls {
+            writeln!(map, "wall {} {}", coord(a), coord(b)).unwrap();
+        }
+
+        for portal in &self.portals {
+            writeln!(
+                map,
+                "portal {} {} {}",
+                coord(&portal.a),
+                coord(&portal.b),
+                portal.length
+            )
+            .unwrap();
+        }
+
+        for door in self.schedule.doors() {
+            writeln!(
+                map,
+                "door {} {} {} {}",
+                coord(&door.coord),
+                door.period,
+                door.open,
+                door.phase
+            )
+            .unwrap();
+        }
+
+        for moving_obstacle in self.schedule.moving_obstacles() {
+            let trajectory = moving_obstacle
+                .trajectory
+                .iter()
+                .map(coord)
+                .collect::<Vec<_>>();
+            writeln!(map, "moving_obstacle {}", trajectory.join(" ")).unwrap();
+        }
+
+        let mut costs = self.schedule.costs().iter().collect::<Vec<_>>();
+        costs.sort();
+        for (point_coord, costs) in costs {
+            let costs = costs.iter().map(u32::to_string).collect::<Vec<_>>();
+            writeln!(map, "costs {} {}", coord(point_coord), costs.join(" ")).unwrap();
+        }
+
+        map
+    }
+
+    pub fn from_map_file(map: &str) -> anyhow::Result<Self> {
+        let mut lines = map.lines().filter(|line| !line.trim().is_empty());
+        let mut header = |key: &str| -> anyhow::Result<SplitWhitespace> {
+            let mut words = lines
+                .next()
+                .context("Unexpected end of the map")?
+                .split_whitespace();
+            if words.next() != Some(key) {
+                bail!("Expected `{key}`");
+            }
+
+            Ok(words)
+        };
+
+        let mut size = header("size")?;
+        let (layers, n, m) = (
+            number(&mut size)? as usize,
+            number(&mut size)? as usize,
+            number(&mut size)? as usize,
+        );
+        if layers == 0 || n == 0 || m == 0 {
+            bail!("Empty map");
+        }
+
+        let topology = match header("topology")?.collect::<Vec<_>>()[..] {
+            ["square"] => Topology::Square,
+            ["pointy_top"] => Topology::Hex(HexOrientation::PointyTop),
+            ["flat_top"] => Topology::Hex(HexOrientation::FlatTop),
+            _ => bail!("Unknown topology"),
+        };
+        let wrap = match header("wrap")?.next() {
+            Some("true") => true,
+            Some("false") => false,
+            _ => bail!("Expected `true` or `false` wrap"),
+        };
+        let stairs_length = number(&mut header("stairs_length")?)?;
+
+        let mut mtx = vec![];
+        for _ in 0..layers {
+            let mut layer = vec![];
+            for _ in 0..n {
+                let row = lines.next().context("Missing rows of cells")?.trim();
+                let row = row.chars().map(point).collect::<anyhow::Result<Vec<_>>>()?;
+                if row.len() != m {
+                    bail!("Row of {} cells instead of {m}", row.len());
+                }
+
+                layer.push(row);
+            }
+            mtx.push(layer);
+        }
+
+        let mut graph = Graph::new_layered(mtx);
+        graph.set_topology(topology);
+        graph.set_wrap(wrap);
+        graph.set_stairs_length(stairs_length);
+
+        for line in lines {
+            let mut words = line.split_whitespace();
+
+            match words.next().unwrap_or_default() {
+                "terrain" => {
+                    let point_coord = graph.parse_coord(&mut words)?;
+                    let cost = positive(&mut words)?;
+                    graph.set_point(&point_coord, Point::Terrain { cost });
+                }
+                "one_way" => {
+                    let point_coord = graph.parse_coord(&mut words)?;
+                    let exit = graph.parse_coord(&mut words)?;
+                    graph.set_one_way(point_coord, exit);
+                }
+                "wall" => {
+                    let a = graph.parse_coord(&mut words)?;
+                    let b = graph.parse_coord(&mut words)?;
+                    graph.add_wall(a, b);
+                }
+                "portal" => {
+                    let a = graph.parse_coord(&mut words)?;
+                    let b = graph.parse_coord(&mut words)?;
+                    let length = positive(&mut words)?;
+                    graph.add_portal(Portal { a, b, length });
+                }
+                "door" => {
+                    let coord = graph.parse_coord(&mut words)?;
+                    let period = positive(&mut words)?;
+                    let open = positive(&mut words)?;
+                    let phase = number(&mut words)?;
+                    graph.schedule.add_door(Door {
+                        coord,
+                        period,
+                        open,
+                        phase,
+                    });
+                }
+                "moving_obstacle" => {
+                    let mut trajectory = vec![];
+                    while words.clone().next().is_some() {
+                        trajectory.push(graph.parse_coord(&mut words)?);
+                    }
+                    if trajectory.is_empty() {
+                        bail!("Empty trajectory");
+                    }
+
+                    graph
+                        .schedule
+                        .add_moving_obstacle(MovingObstacle { trajectory });
+                }
+                "costs" => {
+                    let point_coord = graph.parse_coord(&mut words)?;
+                    let mut costs = vec![];
+                    while words.clone().next().is_some() {
+                        costs.push(positive(&mut words)?);
+                    }
+                    if costs.is_empty() {
+                        bail!("No costs");
+                    }
+
+                    graph.schedule.set_costs(point_coord, costs);
+                }
+                _ => bail!("Unknown line `{line}`"),
+            }
+        }
+
+        Ok(graph)
+    }
+
+    fn parse_coord(&self, words: &mut SplitWhitespace) -> anyhow::Result<PointCoord> {
+        let point_coord = PointCoord {
+            y: number(words)? as usize,
+            x: number(words)? as usize,
+            layer: number(words)? as usize,
+        };
+        if point_coord.y >= self.n || point_coord.x >= self.m || point_coord.layer >= self.layers {
+            bail!("Cell {point_coord} is out of the map");
+        }
+
+        Ok(point_coord)
+    }
+}
+
+fn topology_name(topology: Topology) -> &'static str {
+    match topology {
+        Topology::Square => "square",
+        Topology::Hex(HexOrientation::PointyTop) => "pointy_top",
+        Topology::Hex(HexOrientation::FlatTop) => "flat_top",
+    }
+}
+
+fn coord(point_coord: &PointCoord) -> String {
+    format!("{} {} {}", point_coord.y, point_coord.x, point_coord.layer)
+}
+
+// Inverse of `Point`'s `Display`, terrain costs come on their own lines
+fn point(symbol: char) -> anyhow::Result<Point> {
+    Ok(match symbol {
+        'S' => Point::Start,
+        'E' => Point::End,
+        '.' => Point::Free,
+        'W' => Point::Waypoint,
+        'P' => Point::Portal,
+        'H' => Point::Stairs,
+        '~' => Point::Terrain { cost: 1 },
+        '0'..='3' => Point::Obstacle {
+            length: symbol.to_digit(10).unwrap(),
+        },
+        _ => bail!("Unknown cell `{symbol}`"),
+    })
+}
+
+fn number(words: &mut SplitWhitespace) -> anyhow::Result<u32> {
+    let word = words.next().context("Missing number")?;
+
+    word.parse()
+        .with_context(|| format!("`{word}` is not a number"))
+}
+
+fn positive(words: &mut SplitWhitespace) -> anyhow::Result<u32> {
+    match number(words)? {
+        0 => bail!("Expected a positive number"),
+        number => Ok(number),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Generation;
+
+    fn assert_round_trip(graph: &Graph) {
+        let loaded = Graph::from_map_file(&graph.to_map_file()).unwrap();
+
+        assert!(&loaded == graph, "{}", graph.to_map_file());
+    }
+
+    #[test]
+    fn saved_map_loads_back() {
+        for generation in [
+            Generation::Predefined1,
+            Generation::Predefined2,
+            Generation::Predefined3,
+            Generation::Predefined4,
+            Generation::Predefined5,
+            Generation::Predefined6,
+            Generation::Predefined7,
+            Generation::Terrain,
+            Generation::Dynamic,
+            Generation::Random,
+            Generation::RandomWaypoints,
+        ] {
+            assert_round_trip(&Graph::generate_graph(generation));
+        }
+    }
+
+    #[test]
+    fn saved_map_keeps_settings() {
+        let mut graph = Graph::generate_graph(Generation::Predefined7);
+        graph.set_topology(Topology::Hex(HexOrientation::FlatTop));
+        graph.set_wrap(true);
+        graph.set_stairs_length(5);
+        // Too costly to be packed into a byte
+        graph.set_point(
+            &PointCoord {
+                x: 0,
+                y: 0,
+                layer: 0,
+            },
+            Point::Terrain { cost: 1000 },
+        );
+
+        assert_round_trip(&graph);
+    }
+
+    #[test]
+    fn broken_map_is_an_error() {
+        let map = Graph::generate_graph(Generation::Predefined1).to_map_file();
+
+        assert!(Graph::from_map_file("").is_err());
+        assert!(Graph::from_map_file(&map.replacen('.', "?", 1)).is_err());
+        assert!(Graph::from_map_file(&format!("{map}wall 0 0 0 99 0 0\n")).is_err());
+    }
+}
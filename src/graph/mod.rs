@@ -1,10 +1,31 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
 mod generation;
 mod graph_wrapper;
+mod history;
+mod map_file;
+mod network;
+mod reservation_table;
+mod schedule;
+mod search_events;
+mod search_graph;
+mod search_overlay;
+mod storage;
+mod topology;
 
 pub(crate) use generation::Generation;
 pub(crate) use graph_wrapper::{GraphPath, GraphWrapper};
+pub(crate) use history::{History, Progress};
+pub(crate) use network::Network;
+pub(crate) use reservation_table::{ReservationTable, TimedPointCoord};
+pub(crate) use schedule::{Door, MovingObstacle, Schedule};
+pub(crate) use search_events::{EventLogger, SearchEvent, SearchMetrics, SearchObserver};
+pub(crate) use search_graph::SearchGraph;
+pub(crate) use search_overlay::{Mark, SearchOverlay};
+pub(crate) use storage::{Chunked, PackedPoints, CHUNK_LEN};
+pub(crate) use topology::{AxialCoord, HexOrientation, Topology};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 // #[rustfmt::skip]
 // pub enum Point {
 //     Start    { x: usize, y: usize },
@@ -18,99 +39,483 @@ pub enum Point {
     Start,
     End,
     Free,
-    Path { initial_point: Box<Point> },
-    Seen { initial_point: Box<Point> },
+    Waypoint,
+    // Teleporter, see `Portal`
+    Portal,
+    // Leads to the stairs right above and below it on the neighbor layers
+    Stairs,
+    // Passable cell of any positive cost, e.g. from a heightmap
+    Terrain { cost: u32 },
     Obstacle { length: u32 },
 }
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PointCoord {
     pub x: usize,
     pub y: usize,
+    // Floor of a multi-level graph
+    pub layer: usize,
+}
+
+// Pair of portal cells, either can be moved to from the other one for `length`.
+// Cells on different layers make an elevator.
+#[derive(Clone, PartialEq)]
+pub struct Portal {
+    pub a: PointCoord,
+    pub b: PointCoord,
+    pub length: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Graph {
-    mtx: Vec<Vec<Point>>,
+    // Stacked layers of the same size, one byte per cell in `(layer, y, x)` order
+    points: PackedPoints,
+    layers: usize,
+    n: usize,
+    m: usize,
+
+    topology: Topology,
+    // Edges are glued to the opposite ones (torus)
+    wrap: bool,
+
+    // One-way cells (conveyor belts) with the only neighbor they can be left to
+    one_way: HashMap<PointCoord, PointCoord>,
+    // Thin walls between adjacent cells, the lesser coordinate first
+    walls: HashSet<(PointCoord, PointCoord)>,
+    portals: Vec<Portal>,
+    // Length of going one layer up or down the stairs
+    stairs_length: u32,
+    // Stairs cells, kept along with `points` so they are never searched for
+    stairs: BTreeSet<PointCoord>,
+    // Doors, moving obstacles and costs changing over time, only time-aware searches follow it
+    schedule: Schedule,
 }
 
 impl Graph {
     pub const OBSTACLE_DIFFICULTY_K: u32 = 6;
+    pub const STAIRS_LENGTH: u32 = 3;
+    // Side of a square tile of cells stored in one chunk of a search overlay,
+    // a search going any direction touches few chunks
+    const TILE: usize = 64;
+}
+
+const _: () = assert!(Graph::TILE * Graph::TILE == CHUNK_LEN);
 
-    const DIRS: &[(i32, i32)] = &[(0, 1), (0, -1), (1, 0), (-1, 0)];
+impl Portal {
+    pub fn exit_from(&self, point_coord: &PointCoord) -> Option<&PointCoord> {
+        if &self.a == point_coord {
+            Some(&self.b)
+        } else if &self.b == point_coord {
+            Some(&self.a)
+        } else {
+            None
+        }
+    }
 }
 
-impl Graph {
-    pub fn reset(&mut self) {
-        for row in &mut self.mtx {
-            for point in row {
-                match point {
-                    Point::Path { initial_point } => *point = *initial_point.clone(),
-                    Point::Seen { initial_point } => *point = *initial_point.clone(),
-                    _ => {}
-                }
+impl Point {
+    // Length of moving onto the point, `None` if it can't be entered
+    pub fn move_length(&self) -> Option<u32> {
+        match self {
+            Point::Free => Some(1),
+            Point::Waypoint => Some(1),
+            Point::Portal => Some(1),
+            Point::Stairs => Some(1),
+            Point::Terrain { cost } => {
+                assert!(cost > &0);
+                Some(*cost)
             }
+            Point::End => Some(1),
+            Point::Obstacle {
+                length: point_length,
+            } => {
+                assert!(point_length < &4);
+                Some((point_length + 1) * Graph::OBSTACLE_DIFFICULTY_K)
+            }
+            _ => None,
+        }
+    }
+
+    // Entered on the way to an end, searches mark it as seen once expanded
+    pub fn is_on_the_way(&self) -> bool {
+        self.move_length().is_some() && !matches!(self, Point::End)
+    }
+}
+
+impl Graph {
+    pub fn new(mtx: Vec<Vec<Point>>) -> Self {
+        Self::new_layered(vec![mtx])
+    }
+
+    pub fn new_layered(mtx: Vec<Vec<Vec<Point>>>) -> Self {
+        assert!(
+            mtx.iter().all(|layer| layer.len() == mtx[0].len()
+                && layer.iter().all(|row| row.len() == mtx[0][0].len())),
+            "Layers are of different sizes"
+        );
+
+        let (layers, n, m) = (mtx.len(), mtx[0].len(), mtx[0][0].len());
+        let points = PackedPoints::new(mtx.into_iter().flatten().flatten());
+
+        let mut graph = Graph {
+            points,
+            layers,
+            n,
+            m,
+            topology: Topology::Square,
+            wrap: false,
+            one_way: HashMap::new(),
+            walls: HashSet::new(),
+            portals: vec![],
+            stairs_length: Self::STAIRS_LENGTH,
+            stairs: BTreeSet::new(),
+            schedule: Schedule::default(),
+        };
+        graph.stairs = BTreeSet::from_iter(graph.points_of(&Point::Stairs));
+
+        graph
+    }
+
+    fn index(&self, point_coord: &PointCoord) -> usize {
+        (point_coord.layer * self.n + point_coord.y) * self.m + point_coord.x
+    }
+
+    fn coord_of(&self, index: usize) -> PointCoord {
+        PointCoord {
+            x: index % self.m,
+            y: index / self.m % self.n,
+            layer: index / (self.n * self.m),
         }
     }
 
-    pub fn point_at_mut<'a>(&'a mut self, point_coord: &PointCoord) -> &'a mut Point {
-        &mut self.mtx[point_coord.y][point_coord.x]
+    // Index of the cell's tile followed by the cell's index in the tile,
+    // the last tiles of a layer are padded up to the full size
+    fn tiled_index(&self, point_coord: &PointCoord) -> usize {
+        let (tiles_y, tiles_x) = self.tiles();
+        let tile = (point_coord.layer * tiles_y + point_coord.y / Self::TILE) * tiles_x
+            + point_coord.x / Self::TILE;
+
+        (tile * Self::TILE + point_coord.y % Self::TILE) * Self::TILE + point_coord.x % Self::TILE
+    }
+
+    fn coord_of_tiled(&self, index: usize) -> PointCoord {
+        let (tiles_y, tiles_x) = self.tiles();
+        let tile = index / (Self::TILE * Self::TILE);
+
+        PointCoord {
+            x: tile % tiles_x * Self::TILE + index % Self::TILE,
+            y: tile / tiles_x % tiles_y * Self::TILE + index / Self::TILE % Self::TILE,
+            layer: tile / (tiles_x * tiles_y),
+        }
+    }
+
+    fn tiles(&self) -> (usize, usize) {
+        (self.n.div_ceil(Self::TILE), self.m.div_ceil(Self::TILE))
+    }
+
+    pub fn point_at(&self, point_coord: &PointCoord) -> Point {
+        self.points.get(self.index(point_coord))
+    }
+
+    pub fn set_point(&mut self, point_coord: &PointCoord, point: Point) {
+        let index = self.index(point_coord);
+
+        if point == Point::Stairs {
+            self.stairs.insert(point_coord.clone());
+        } else {
+            self.stairs.remove(point_coord);
+        }
+        self.points.set(index, point);
     }
 
-    pub fn point_at<'a>(&'a self, point_coord: &PointCoord) -> &'a Point {
-        &self.mtx[point_coord.y][point_coord.x]
+    // Bytes taken by the points
+    pub fn memory_usage(&self) -> usize {
+        self.points.memory_usage()
     }
 
     pub fn neighbors<'a, 'b>(
         &'a self,
         point_coord: &'b PointCoord,
-    ) -> impl Iterator<Item = (&'a Point, PointCoord)> + 'b
+    ) -> impl Iterator<Item = (Point, PointCoord)> + 'b
     where
         'a: 'b,
     {
-        Self::DIRS.iter().filter_map(move |(di, dj)| {
-            let to_i = (point_coord.y as i32 + di) as usize;
-            let to_j = (point_coord.x as i32 + dj) as usize;
+        let portal_exits = self
+            .portals
+            .iter()
+            .filter_map(|portal| portal.exit_from(point_coord))
+            .cloned();
+
+        self.topology
+            .neighbor_coords(point_coord)
+            .into_iter()
+            .flatten()
+            .filter_map(|offset| self.coord_at(point_coord.layer, offset))
+            .chain(portal_exits)
+            .chain(self.stairs_exits(point_coord))
+            .filter(|to| self.can_move(point_coord, to))
+            .map(|to| (self.point_at(&to), to))
+    }
+
+    // Cell at `(i, j)` offset coordinates of the layer, wrapped around the edges if the graph wraps
+    pub fn coord_at(&self, layer: usize, (i, j): (i32, i32)) -> Option<PointCoord> {
+        let (n, m) = (self.n() as i32, self.m() as i32);
 
-            let point = self.mtx.get(to_i)?.get(to_j)?;
+        if self.wrap {
+            return Some(PointCoord {
+                y: i.rem_euclid(n) as usize,
+                x: j.rem_euclid(m) as usize,
+                layer,
+            });
+        }
 
-            Some((point, PointCoord { y: to_i, x: to_j }))
+        ((0..n).contains(&i) && (0..m).contains(&j)).then_some(PointCoord {
+            y: i as usize,
+            x: j as usize,
+            layer,
         })
     }
 
+    // Stairs right below and above the stairs at `point_coord`
+    pub fn stairs_exits(&self, point_coord: &PointCoord) -> Vec<PointCoord> {
+        if self.point_at(point_coord) != Point::Stairs {
+            return vec![];
+        }
+
+        [
+            point_coord.layer.checked_sub(1),
+            Some(point_coord.layer + 1),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|&layer| layer < self.layers())
+        .map(|layer| PointCoord {
+            layer,
+            ..point_coord.clone()
+        })
+        .filter(|exit| self.point_at(exit) == Point::Stairs)
+        .collect()
+    }
+
+    // Moves between adjacent cells are blocked by thin walls and one-way cells
+    pub fn can_move(&self, from: &PointCoord, to: &PointCoord) -> bool {
+        !self.walls.contains(&Self::wall_key(from, to))
+            && self.one_way.get(from).is_none_or(|exit| exit == to)
+    }
+
+    // A cell not adjacent to `exit` can't be left at all
+    pub fn set_one_way(&mut self, point_coord: PointCoord, exit: PointCoord) {
+        self.one_way.insert(point_coord, exit);
+    }
+
+    pub fn add_wall(&mut self, a: PointCoord, b: PointCoord) {
+        self.walls.insert(Self::wall_key(&a, &b));
+    }
+
+    // Portal cells get `Point::Portal`
+    pub fn add_portal(&mut self, portal: Portal) {
+        self.set_point(&portal.a, Point::Portal);
+        self.set_point(&portal.b, Point::Portal);
+
+        self.portals.push(portal);
+    }
+
+    pub fn portals(&self) -> &[Portal] {
+        &self.portals
+    }
+
+    // Length of moving from `from` onto `to` (`to_point`), the portal's one if they are paired,
+    // the stairs' one if they are on different layers
+    pub fn move_length(&self, from: &PointCoord, to: &PointCoord, to_point: &Point) -> Option<u32> {
+        Some(self.move_length_of(from, to, to_point.move_length()?))
+    }
+
+    // The same leaving `from` at `t`, the cost of `to` may change over time
+    pub fn move_length_at(
+        &self,
+        from: &PointCoord,
+        to: &PointCoord,
+        to_point: &Point,
+        t: u32,
+    ) -> Option<u32> {
+        let length = to_point.move_length()?;
+
+        Some(self.move_length_of(from, to, self.schedule.cost_at(to, t).unwrap_or(length)))
+    }
+
+    // `length` of `to` itself unless the move goes through a portal or stairs
+    fn move_length_of(&self, from: &PointCoord, to: &PointCoord, length: u32) -> u32 {
+        if let Some(portal) = self
+            .portals
+            .iter()
+            .find(|portal| portal.exit_from(from) == Some(to))
+        {
+            return portal.length;
+        }
+
+        if from.layer != to.layer {
+            return self.stairs_length;
+        }
+
+        length
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    pub fn schedule_mut(&mut self) -> &mut Schedule {
+        &mut self.schedule
+    }
+
+    pub fn one_way(&self) -> &HashMap<PointCoord, PointCoord> {
+        &self.one_way
+    }
+
+    pub fn has_wall(&self, a: &PointCoord, b: &PointCoord) -> bool {
+        self.walls.contains(&Self::wall_key(a, b))
+    }
+
+    fn wall_key(a: &PointCoord, b: &PointCoord) -> (PointCoord, PointCoord) {
+        (a.min(b).clone(), a.max(b).clone())
+    }
+
+    // Least length between `a` and `b` if there were no obstacles
+    pub fn distance(&self, a: &PointCoord, b: &PointCoord) -> u32 {
+        self.distance_through_portals(a, b, |portal| portal.length, self.stairs_length)
+    }
+
+    // Least number of moves between `a` and `b` if there were no obstacles
+    pub fn moves(&self, a: &PointCoord, b: &PointCoord) -> u32 {
+        self.distance_through_portals(a, b, |_| 1, 1)
+    }
+
+    // Least number of moves between `a` and `b` on an empty layer without portals
+    pub fn grid_distance(&self, a: &PointCoord, b: &PointCoord) -> u32 {
+        if self.wrap {
+            self.topology.wrapped_distance(a, b, (self.n(), self.m()))
+        } else {
+            self.topology.distance(a, b)
+        }
+    }
+
+    // Dijkstra over the ends of portals, any of them may shorten the way.
+    // Every layer between the points takes at least `stairs_length`.
+    fn distance_through_portals(
+        &self,
+        a: &PointCoord,
+        b: &PointCoord,
+        portal_length: impl Fn(&Portal) -> u32,
+        stairs_length: u32,
+    ) -> u32 {
+        let walk = |a: &PointCoord, b: &PointCoord| {
+            self.grid_distance(a, b) + a.layer.abs_diff(b.layer) as u32 * stairs_length
+        };
+
+        let direct = walk(a, b);
+        if self.portals.is_empty() {
+            return direct;
+        }
+
+        // Ends of the same portal are `2 * k` and `2 * k + 1`
+        let ends = self
+            .portals
+            .iter()
+            .flat_map(|portal| [&portal.a, &portal.b])
+            .collect::<Vec<_>>();
+
+        let mut lengths = ends.iter().map(|end| walk(a, end)).collect::<Vec<_>>();
+        let mut done = vec![false; ends.len()];
+
+        while let Some(cur) = (0..ends.len())
+            .filter(|&i| !done[i])
+            .min_by_key(|&i| lengths[i])
+        {
+            done[cur] = true;
+
+            let through_portal = lengths[cur] + portal_length(&self.portals[cur / 2]);
+            lengths[cur ^ 1] = lengths[cur ^ 1].min(through_portal);
+
+            for to in 0..ends.len() {
+                let walked = lengths[cur] + walk(ends[cur], ends[to]);
+                lengths[to] = lengths[to].min(walked);
+            }
+        }
+
+        ends.iter()
+            .zip(lengths)
+            .map(|(end, length)| length + walk(end, b))
+            .fold(direct, u32::min)
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn wrap(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    pub fn stairs_length(&self) -> u32 {
+        self.stairs_length
+    }
+
+    pub fn set_stairs_length(&mut self, stairs_length: u32) {
+        self.stairs_length = stairs_length;
+    }
+
+    pub fn distance_name(&self) -> &'static str {
+        self.topology.distance_name(self.wrap)
+    }
+
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
     pub fn n(&self) -> usize {
-        self.mtx.len()
+        self.n
     }
 
     pub fn m(&self) -> usize {
-        self.mtx[0].len()
+        self.m
     }
 }
 
 impl std::fmt::Display for Point {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match &self {
-                Point::Start => "S",
-                Point::End => "E",
-                Point::Free => ".",
-                Point::Path { .. } => "*",
-                Point::Obstacle { .. } => "X",
-                Point::Seen { .. } => "O",
-            },
-        )
+        match &self {
+            Point::Start => write!(f, "S"),
+            Point::End => write!(f, "E"),
+            Point::Free => write!(f, "."),
+            Point::Waypoint => write!(f, "W"),
+            Point::Portal => write!(f, "P"),
+            Point::Stairs => write!(f, "H"),
+            Point::Terrain { .. } => write!(f, "~"),
+            // Difficulty of the obstacle
+            Point::Obstacle { length } => write!(f, "{length}"),
+        }
     }
 }
 
 impl std::fmt::Display for Graph {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.mtx {
-            for point in row {
-                write!(f, "{}", point)?;
+        for index in 0..self.points.len() {
+            if index > 0 && index % (self.n * self.m) == 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", self.points.get(index))?;
+            if index % self.m == self.m - 1 {
+                writeln!(f)?;
             }
-            write!(f, "\n")?;
         }
 
         Ok(())
@@ -119,7 +524,7 @@ impl std::fmt::Display for Graph {
 
 impl std::fmt::Display for PointCoord {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "y={}, x={}", self.y, self.x)
+        write!(f, "y={}, x={}, layer={}", self.y, self.x, self.layer)
     }
 }
 
@@ -127,33 +532,97 @@ impl Graph {
     pub const N: usize = 10;
     pub const M: usize = 10;
 
-    pub fn start(&self) -> Option<PointCoord> {
-        let mut start: Option<PointCoord> = None;
+    pub fn starts(&self) -> Vec<PointCoord> {
+        self.points_of(&Point::Start)
+    }
 
-        'out: for (i_, row_) in self.mtx.iter().enumerate() {
-            for (j_, point) in row_.iter().enumerate() {
-                if point == &Point::Start {
-                    start = Some(PointCoord { x: j_, y: i_ });
-                    break 'out;
-                }
-            }
-        }
+    pub fn ends(&self) -> Vec<PointCoord> {
+        self.points_of(&Point::End)
+    }
 
-        start
+    // Points the path has to visit between `start` and `end`, in any order
+    pub fn waypoints(&self) -> Vec<PointCoord> {
+        self.points_of(&Point::Waypoint)
     }
 
-    pub fn end(&self) -> Option<PointCoord> {
-        let mut end: Option<PointCoord> = None;
+    // The highest cost of a terrain cell, 1 if there are none
+    pub fn max_terrain_cost(&self) -> u32 {
+        self.points
+            .iter()
+            .filter_map(|point| match point {
+                Point::Terrain { cost } => Some(cost),
+                _ => None,
+            })
+            .fold(1, u32::max)
+    }
 
-        'out: for (i_, row_) in self.mtx.iter().enumerate() {
-            for (j_, point) in row_.iter().enumerate() {
-                if point == &Point::End {
-                    end = Some(PointCoord { x: j_, y: i_ });
-                    break 'out;
-                }
-            }
-        }
+    pub fn stairs(&self) -> impl Iterator<Item = &PointCoord> {
+        self.stairs.iter()
+    }
+
+    fn points_of(&self, kind: &Point) -> Vec<PointCoord> {
+        self.points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| point == kind)
+            .map(|(index, _)| self.coord_of(index))
+            .collect()
+    }
+}
+
+impl SearchGraph for Graph {
+    type Node = PointCoord;
+
+    fn starts(&self) -> Vec<PointCoord> {
+        Graph::starts(self)
+    }
+
+    fn ends(&self) -> Vec<PointCoord> {
+        Graph::ends(self)
+    }
+
+    fn edges(&self, point_coord: &PointCoord) -> Vec<(PointCoord, u32)> {
+        self.neighbors(point_coord)
+            .filter_map(|(to_point, to)| {
+                let length = self.move_length(point_coord, &to, &to_point)?;
+                Some((to, length))
+            })
+            .collect()
+    }
+
+    // Obstacles only slow down the weighted searches
+    fn is_passable(&self, point_coord: &PointCoord) -> bool {
+        !matches!(self.point_at(point_coord), Point::Obstacle { .. })
+    }
+
+    fn coords(&self, point_coord: &PointCoord) -> Option<(f32, f32)> {
+        Some((point_coord.x as f32, point_coord.y as f32))
+    }
+
+    fn heuristic(&self, a: &PointCoord, b: &PointCoord) -> u32 {
+        self.distance(a, b)
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        self.distance_name()
+    }
+
+    // Every tile is counted in full
+    fn node_count(&self) -> usize {
+        let (tiles_y, tiles_x) = self.tiles();
+
+        self.layers * tiles_y * tiles_x * Self::TILE * Self::TILE
+    }
+
+    fn node_index(&self, point_coord: &PointCoord) -> usize {
+        self.tiled_index(point_coord)
+    }
+
+    fn node_at(&self, index: usize) -> PointCoord {
+        self.coord_of_tiled(index)
+    }
 
-        end
+    fn memory_usage(&self) -> usize {
+        Graph::memory_usage(self)
     }
 }
@@ -0,0 +1,119 @@
+use rand::Rng;
+
+use super::SearchGraph;
+
+// Graph given by adjacency lists, e.g. a road network. Nodes are indices.
+#[derive(Clone)]
+pub struct Network {
+    coords: Vec<Option<(f32, f32)>>,
+    adjacency: Vec<Vec<(usize, u32)>>,
+
+    starts: Vec<usize>,
+    ends: Vec<usize>,
+}
+
+impl Network {
+    pub const NODES: usize = 40;
+    pub const WIDTH: f32 = 100.0;
+    pub const HEIGHT: f32 = 75.0;
+    // Every node is connected to this number of its nearest nodes
+    const NEAREST: usize = 3;
+
+    // Nodes scattered over the plane, roads are a bit longer than straight lines between them
+    pub fn generate_random() -> Self {
+        println!("[I] generate_random_network");
+
+        let mut rng = rand::thread_rng();
+
+        let mut coords = (0..Self::NODES)
+            .map(|_| {
+                (
+                    rng.gen_range(0.0..Self::WIDTH),
+                    rng.gen_range(0.0..Self::HEIGHT),
+                )
+            })
+            .collect::<Vec<_>>();
+        coords.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let distance = |a: usize, b: usize| {
+            let ((a_x, a_y), (b_x, b_y)) = (coords[a], coords[b]);
+            (a_x - b_x).hypot(a_y - b_y)
+        };
+
+        let mut adjacency = vec![vec![]; Self::NODES];
+        let mut connect = |a: usize, b: usize, rng: &mut rand::rngs::ThreadRng| {
+            if a == b || adjacency[a].iter().any(|&(to, _)| to == b) {
+                return;
+            }
+
+            let length = (distance(a, b) * rng.gen_range(1.0..1.5)).ceil() as u32;
+            adjacency[a].push((b, length));
+            adjacency[b].push((a, length));
+        };
+
+        for a in 0..Self::NODES {
+            let mut nearest = (0..Self::NODES).collect::<Vec<_>>();
+            nearest.sort_by(|&b, &c| distance(a, b).total_cmp(&distance(a, c)));
+
+            for &b in nearest.iter().skip(1).take(Self::NEAREST) {
+                connect(a, b, &mut rng);
+            }
+
+            // Connected to the nearest of the previous (left) ones, so every node is reachable
+            if let Some(&b) = nearest.iter().find(|&&b| b < a) {
+                connect(a, b, &mut rng);
+            }
+        }
+
+        Network {
+            coords: coords.into_iter().map(Some).collect(),
+            adjacency,
+            starts: vec![0],
+            ends: vec![Self::NODES - 1],
+        }
+    }
+
+    pub fn adjacency(&self, node: usize) -> &[(usize, u32)] {
+        &self.adjacency[node]
+    }
+}
+
+impl SearchGraph for Network {
+    type Node = usize;
+
+    fn starts(&self) -> Vec<usize> {
+        self.starts.clone()
+    }
+
+    fn ends(&self) -> Vec<usize> {
+        self.ends.clone()
+    }
+
+    fn edges(&self, node: &usize) -> Vec<(usize, u32)> {
+        self.adjacency[*node].clone()
+    }
+
+    fn coords(&self, node: &usize) -> Option<(f32, f32)> {
+        self.coords[*node]
+    }
+
+    fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn node_index(&self, node: &usize) -> usize {
+        *node
+    }
+
+    fn node_at(&self, index: usize) -> usize {
+        index
+    }
+
+    fn memory_usage(&self) -> usize {
+        let edges = self.adjacency.iter().map(Vec::capacity).sum::<usize>();
+
+        self.coords.capacity() * std::mem::size_of::<Option<(f32, f32)>>()
+            + self.adjacency.capacity() * std::mem::size_of::<Vec<(usize, u32)>>()
+            + edges * std::mem::size_of::<(usize, u32)>()
+    }
+}
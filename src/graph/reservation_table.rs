@@ -0,0 +1,64 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::PointCoord;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimedPointCoord {
+    pub coord: PointCoord,
+    pub t: u32,
+}
+
+// Cells (and moves between cells) occupied by already planned agents
+#[derive(Clone, Default)]
+pub struct ReservationTable {
+    cells: HashSet<(PointCoord, u32)>,
+    // `from` -> `to` moves started at `t`
+    moves: HashSet<(PointCoord, PointCoord, u32)>,
+    // Agents stay at their goal forever once they reached it
+    parked: HashMap<PointCoord, u32>,
+
+    horizon: u32,
+}
+
+impl ReservationTable {
+    pub fn reserve_path(&mut self, path: &[TimedPointCoord]) {
+        for timed_point in path {
+            self.cells
+                .insert((timed_point.coord.clone(), timed_point.t));
+            self.horizon = self.horizon.max(timed_point.t);
+        }
+
+        for moving in path.windows(2) {
+            self.moves.insert((
+                moving[0].coord.clone(),
+                moving[1].coord.clone(),
+                moving[0].t,
+            ));
+        }
+
+        if let Some(last) = path.last() {
+            self.parked.insert(last.coord.clone(), last.t);
+        }
+    }
+
+    pub fn is_reserved(&self, point_coord: &PointCoord, t: u32) -> bool {
+        self.cells.contains(&(point_coord.clone(), t))
+            || matches!(self.parked.get(point_coord), Some(&parked_t) if parked_t <= t)
+    }
+
+    // Moving `from` -> `to` at `t` swaps places with an agent moving `to` -> `from`
+    pub fn is_move_reserved(&self, from: &PointCoord, to: &PointCoord, t: u32) -> bool {
+        self.moves.contains(&(to.clone(), from.clone(), t))
+    }
+
+    // Whether `point_coord` is reserved at `t` or at any moment after it
+    pub fn is_reserved_since(&self, point_coord: &PointCoord, t: u32) -> bool {
+        self.parked.contains_key(point_coord)
+            || (t..=self.horizon).any(|t| self.cells.contains(&(point_coord.clone(), t)))
+    }
+
+    // Last timestep with a reservation, after it only parked agents remain
+    pub fn horizon(&self) -> u32 {
+        self.horizon
+    }
+}
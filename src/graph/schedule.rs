@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use super::PointCoord;
+
+// Cell that is open for the first `open` timesteps of every `period`, shifted by `phase`
+#[derive(Clone, PartialEq)]
+pub struct Door {
+    pub coord: PointCoord,
+    pub period: u32,
+    pub open: u32,
+    pub phase: u32,
+}
+
+// Obstacle going one cell of its looped `trajectory` further every timestep
+#[derive(Clone, PartialEq)]
+pub struct MovingObstacle {
+    pub trajectory: Vec<PointCoord>,
+}
+
+// Changes of the graph over time, the search leaves `start` at timestep 0
+#[derive(Clone, Default, PartialEq)]
+pub struct Schedule {
+    doors: Vec<Door>,
+    moving_obstacles: Vec<MovingObstacle>,
+    // Cost of moving onto the cell at `t` is `costs[t % costs.len()]`
+    costs: HashMap<PointCoord, Vec<u32>>,
+}
+
+impl Door {
+    pub fn is_open_at(&self, t: u32) -> bool {
+        (t + self.phase) % self.period < self.open
+    }
+}
+
+impl MovingObstacle {
+    pub fn coord_at(&self, t: u32) -> &PointCoord {
+        &self.trajectory[t as usize % self.trajectory.len()]
+    }
+}
+
+impl Schedule {
+    pub fn add_door(&mut self, door: Door) {
+        assert!(door.period > 0 && door.open > 0, "Door is never open");
+
+        self.doors.push(door);
+    }
+
+    pub fn add_moving_obstacle(&mut self, moving_obstacle: MovingObstacle) {
+        assert!(!moving_obstacle.trajectory.is_empty(), "Empty trajectory");
+
+        self.moving_obstacles.push(moving_obstacle);
+    }
+
+    // Costs are never below 1, so the static heuristics stay admissible
+    pub fn set_costs(&mut self, point_coord: PointCoord, costs: Vec<u32>) {
+        assert!(!costs.is_empty() && costs.iter().all(|&cost| cost > 0));
+
+        self.costs.insert(point_coord, costs);
+    }
+
+    pub fn doors(&self) -> &[Door] {
+        &self.doors
+    }
+
+    pub fn moving_obstacles(&self) -> &[MovingObstacle] {
+        &self.moving_obstacles
+    }
+
+    pub fn costs(&self) -> &HashMap<PointCoord, Vec<u32>> {
+        &self.costs
+    }
+
+    // Neither a closed door nor a moving obstacle is at the cell at `t`
+    pub fn is_free_at(&self, point_coord: &PointCoord, t: u32) -> bool {
+        self.doors
+            .iter()
+            .all(|door| &door.coord != point_coord || door.is_open_at(t))
+            && self
+                .moving_obstacles
+                .iter()
+                .all(|moving_obstacle| moving_obstacle.coord_at(t) != point_coord)
+    }
+
+    // No moving obstacle comes from `to` onto `from` the same timestep the agent
+    // arrives from `from` onto `to`
+    pub fn can_move(&self, from: &PointCoord, to: &PointCoord, arrival: u32) -> bool {
+        arrival == 0
+            || self.moving_obstacles.iter().all(|moving_obstacle| {
+                moving_obstacle.coord_at(arrival - 1) != to
+                    || moving_obstacle.coord_at(arrival) != from
+            })
+    }
+
+    // Cost of moving onto the cell at `t`, `None` if it doesn't change over time
+    pub fn cost_at(&self, point_coord: &PointCoord, t: u32) -> Option<u32> {
+        let costs = self.costs.get(point_coord)?;
+
+        Some(costs[t as usize % costs.len()])
+    }
+
+    // Everything repeats after this many timesteps, `u32::MAX` if after even more
+    pub fn period(&self) -> u32 {
+        let mut periods = self
+            .doors
+            .iter()
+            .map(|door| door.period)
+            .chain(
+                self.moving_obstacles
+                    .iter()
+                    .map(|moving_obstacle| moving_obstacle.trajectory.len() as u32),
+            )
+            .chain(self.costs.values().map(|costs| costs.len() as u32));
+
+        periods
+            .try_fold(1u32, |period, other| {
+                (period / gcd(period, other)).checked_mul(other)
+            })
+            .unwrap_or(u32::MAX)
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn door(period: u32) -> Door {
+        Door {
+            coord: PointCoord {
+                x: 0,
+                y: 0,
+                layer: 0,
+            },
+            period,
+            open: 1,
+            phase: 0,
+        }
+    }
+
+    #[test]
+    fn period_is_least_common_multiple() {
+        let mut schedule = Schedule::default();
+        for period in [4, 6, 10] {
+            schedule.add_door(door(period));
+        }
+
+        assert_eq!(schedule.period(), 60);
+    }
+
+    #[test]
+    fn too_long_period_is_capped() {
+        let mut schedule = Schedule::default();
+        for period in [65_521, 65_519, 65_497] {
+            schedule.add_door(door(period));
+        }
+
+        assert_eq!(schedule.period(), u32::MAX);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+// What a search does with its frontier, emitted in the order it happens
+#[derive(Clone)]
+pub enum SearchEvent<N> {
+    // Added to the frontier, the lowest priority is taken out first
+    Pushed { node: N, priority: u32 },
+    // Taken out of the frontier to be expanded
+    Popped { node: N, priority: u32 },
+    // Reached from `from` by a path of `length` shorter than the known one
+    Relaxed { from: N, to: N, length: u32 },
+    // Taken out of the frontier after a shorter path to it was found
+    SkippedStale { node: N },
+    GoalReached { node: N, length: u32 },
+    // Started over, collected state is out of date
+    Reset,
+}
+
+// Subscriber to the events of a search, e.g. the UI, a logger or a metrics collector
+pub trait SearchObserver<N> {
+    fn on_event(&mut self, event: &SearchEvent<N>);
+}
+
+// Shared one, so the subscriber may read what it has collected
+impl<N, O: SearchObserver<N>> SearchObserver<N> for Rc<RefCell<O>> {
+    fn on_event(&mut self, event: &SearchEvent<N>) {
+        self.borrow_mut().on_event(event);
+    }
+}
+
+// Prints every event while enabled
+#[derive(Default)]
+pub struct EventLogger {
+    pub enabled: bool,
+}
+
+impl<N: Display> SearchObserver<N> for EventLogger {
+    fn on_event(&mut self, event: &SearchEvent<N>) {
+        if self.enabled {
+            println!("[I] {event}");
+        }
+    }
+}
+
+// Number of the events of every kind
+#[derive(Clone, Default)]
+pub struct SearchMetrics {
+    pub pushed: u32,
+    pub popped: u32,
+    pub relaxed: u32,
+    pub skipped_stale: u32,
+}
+
+impl<N> SearchObserver<N> for SearchMetrics {
+    fn on_event(&mut self, event: &SearchEvent<N>) {
+        match event {
+            SearchEvent::Pushed { .. } => self.pushed += 1,
+            SearchEvent::Popped { .. } => self.popped += 1,
+            SearchEvent::Relaxed { .. } => self.relaxed += 1,
+            SearchEvent::SkippedStale { .. } => self.skipped_stale += 1,
+            SearchEvent::GoalReached { .. } => {}
+            SearchEvent::Reset => *self = SearchMetrics::default(),
+        }
+    }
+}
+
+impl<N: Display> Display for SearchEvent<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchEvent::Pushed { node, priority } => {
+                write!(f, "Pushed [{node}] with priority {priority}")
+            }
+            SearchEvent::Popped { node, priority } => {
+                write!(f, "Popped [{node}] with priority {priority}")
+            }
+            SearchEvent::Relaxed { from, to, length } => {
+                write!(f, "Relaxed [{from}] -> [{to}] to length {length}")
+            }
+            SearchEvent::SkippedStale { node } => write!(f, "Skipped stale [{node}]"),
+            SearchEvent::GoalReached { node, length } => {
+                write!(f, "Reached goal [{node}] with length {length}")
+            }
+            SearchEvent::Reset => write!(f, "Reset"),
+        }
+    }
+}
+
+impl Display for SearchMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Events")?;
+        writeln!(f, "\tPushed:\t\t{}", self.pushed)?;
+        writeln!(f, "\tPopped:\t\t{}", self.popped)?;
+        writeln!(f, "\tRelaxed:\t{}", self.relaxed)?;
+        writeln!(f, "\tSkipped stale:\t{}", self.skipped_stale)
+    }
+}
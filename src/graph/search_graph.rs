@@ -0,0 +1,45 @@
+use std::{fmt::Display, hash::Hash};
+
+// Graph the generic searches run on: nodes with weighted outgoing edges.
+// Searches only read it, their marks are kept in a `SearchOverlay`.
+pub trait SearchGraph: Clone + 'static {
+    type Node: Clone + Eq + Ord + Hash + Display;
+
+    fn starts(&self) -> Vec<Self::Node>;
+
+    fn ends(&self) -> Vec<Self::Node>;
+
+    // Outgoing edges with their lengths
+    fn edges(&self, node: &Self::Node) -> Vec<(Self::Node, u32)>;
+
+    // Searches ignoring the lengths never enter impassable nodes, the others pay their length
+    fn is_passable(&self, _node: &Self::Node) -> bool {
+        true
+    }
+
+    // Position of the node on the plane, `None` if it has no coordinates
+    fn coords(&self, node: &Self::Node) -> Option<(f32, f32)>;
+
+    // Lower bound of the length between `a` and `b`, the straight line
+    // if both have coordinates (edges are never shorter than it)
+    fn heuristic(&self, a: &Self::Node, b: &Self::Node) -> u32 {
+        match (self.coords(a), self.coords(b)) {
+            (Some((a_x, a_y)), Some((b_x, b_y))) => (a_x - b_x).hypot(a_y - b_y) as u32,
+            _ => 0,
+        }
+    }
+
+    fn heuristic_name(&self) -> &'static str {
+        "Euclidean distance"
+    }
+
+    // Nodes are numbered `0..node_count()`, so the search overlay may be stored densely
+    fn node_count(&self) -> usize;
+
+    fn node_index(&self, node: &Self::Node) -> usize;
+
+    fn node_at(&self, index: usize) -> Self::Node;
+
+    // Bytes taken by the graph on the heap
+    fn memory_usage(&self) -> usize;
+}
@@ -0,0 +1,56 @@
+use super::Chunked;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mark {
+    #[default]
+    Unseen,
+    Seen,
+    Pruned,
+    Path,
+}
+
+// State of one search laid over a graph it only reads, by `SearchGraph::node_index`.
+// Searches sharing a graph have an overlay each.
+#[derive(Clone)]
+pub struct SearchOverlay {
+    marks: Chunked<Mark>,
+    // Index of the parent and length of the best known path for every reached node
+    came_from: Chunked<Option<(u32, u32)>>,
+}
+
+impl SearchOverlay {
+    pub fn new(node_count: usize) -> Self {
+        assert!(node_count <= u32::MAX as usize, "Too many nodes to index");
+
+        SearchOverlay {
+            marks: Chunked::new(node_count),
+            came_from: Chunked::new(node_count),
+        }
+    }
+
+    pub fn mark(&self, index: usize) -> Mark {
+        self.marks.get(index)
+    }
+
+    pub fn set_mark(&mut self, index: usize, mark: Mark) {
+        self.marks.set(index, mark);
+    }
+
+    pub fn came_from(&self, index: usize) -> Option<(u32, u32)> {
+        self.came_from.get(index)
+    }
+
+    pub fn set_came_from(&mut self, index: usize, came_from: Option<(u32, u32)>) {
+        self.came_from.set(index, came_from);
+    }
+
+    pub fn clear(&mut self) {
+        self.marks.clear();
+        self.came_from.clear();
+    }
+
+    // Bytes taken on the heap
+    pub fn memory_usage(&self) -> usize {
+        self.marks.memory_usage() + self.came_from.memory_usage()
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use super::Point;
+
+// Points of a graph packed one byte per cell. Terrain costs too high to fit into
+// the byte are kept aside.
+#[derive(Clone, PartialEq)]
+pub struct PackedPoints {
+    codes: Vec<u8>,
+    terrain_costs: HashMap<usize, u32>,
+}
+
+// Values indexed by nodes, stored in chunks allocated on the first write,
+// so a search touching a small part of a huge graph stays small
+#[derive(Clone)]
+pub struct Chunked<T> {
+    len: usize,
+    chunks: Vec<Option<Box<[T]>>>,
+}
+
+impl PackedPoints {
+    const START: u8 = 0;
+    const END: u8 = 1;
+    const FREE: u8 = 2;
+    const WAYPOINT: u8 = 3;
+    const PORTAL: u8 = 4;
+    const STAIRS: u8 = 5;
+    // Followed by the other 3 obstacle lengths
+    const OBSTACLE: u8 = 6;
+    // Terrain of cost `code - TERRAIN + 1`
+    const TERRAIN: u8 = 10;
+    const TERRAIN_ASIDE: u8 = u8::MAX;
+
+    pub fn new(points: impl Iterator<Item = Point>) -> Self {
+        let mut packed = PackedPoints {
+            codes: vec![],
+            terrain_costs: HashMap::new(),
+        };
+
+        for (index, point) in points.enumerate() {
+            packed.codes.push(Self::FREE);
+            packed.set(index, point);
+        }
+        packed.codes.shrink_to_fit();
+
+        packed
+    }
+
+    pub fn get(&self, index: usize) -> Point {
+        match self.codes[index] {
+            Self::START => Point::Start,
+            Self::END => Point::End,
+            Self::FREE => Point::Free,
+            Self::WAYPOINT => Point::Waypoint,
+            Self::PORTAL => Point::Portal,
+            Self::STAIRS => Point::Stairs,
+            code @ Self::OBSTACLE..Self::TERRAIN => Point::Obstacle {
+                length: (code - Self::OBSTACLE) as u32,
+            },
+            Self::TERRAIN_ASIDE => Point::Terrain {
+                cost: self.terrain_costs[&index],
+            },
+            code => Point::Terrain {
+                cost: (code - Self::TERRAIN) as u32 + 1,
+            },
+        }
+    }
+
+    pub fn set(&mut self, index: usize, point: Point) {
+        self.terrain_costs.remove(&index);
+
+        self.codes[index] = match point {
+            Point::Start => Self::START,
+            Point::End => Self::END,
+            Point::Free => Self::FREE,
+            Point::Waypoint => Self::WAYPOINT,
+            Point::Portal => Self::PORTAL,
+            Point::Stairs => Self::STAIRS,
+            Point::Obstacle { length } => {
+                assert!(length < 4);
+                Self::OBSTACLE + length as u8
+            }
+            Point::Terrain { cost } if cost < (Self::TERRAIN_ASIDE - Self::TERRAIN) as u32 => {
+                assert!(cost > 0);
+                Self::TERRAIN + (cost - 1) as u8
+            }
+            Point::Terrain { cost } => {
+                self.terrain_costs.insert(index, cost);
+                Self::TERRAIN_ASIDE
+            }
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+
+    // Bytes taken on the heap
+    pub fn memory_usage(&self) -> usize {
+        self.codes.capacity() + self.terrain_costs.capacity() * std::mem::size_of::<(usize, u32)>()
+    }
+}
+
+// Values in one chunk of `Chunked`
+pub const CHUNK_LEN: usize = 4096;
+
+impl<T: Copy + Default> Chunked<T> {
+    pub fn new(len: usize) -> Self {
+        Chunked {
+            len,
+            chunks: vec![None; len.div_ceil(CHUNK_LEN)],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        assert!(index < self.len);
+
+        match &self.chunks[index / CHUNK_LEN] {
+            Some(chunk) => chunk[index % CHUNK_LEN],
+            None => T::default(),
+        }
+    }
+
+    pub fn set(&mut self, index: usize, value: T) {
+        assert!(index < self.len);
+
+        let chunk = self.chunks[index / CHUNK_LEN]
+            .get_or_insert_with(|| vec![T::default(); CHUNK_LEN].into_boxed_slice());
+        chunk[index % CHUNK_LEN] = value;
+    }
+
+    // Drops all the chunks, every value is default again
+    pub fn clear(&mut self) {
+        self.chunks.iter_mut().for_each(|chunk| *chunk = None);
+    }
+
+    // Bytes taken on the heap
+    pub fn memory_usage(&self) -> usize {
+        let chunk_size = CHUNK_LEN * std::mem::size_of::<T>();
+
+        self.chunks.capacity() * std::mem::size_of::<Option<Box<[T]>>>()
+            + self.chunks.iter().flatten().count() * chunk_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_point_packs_back() {
+        let points = [
+            Point::Start,
+            Point::End,
+            Point::Free,
+            Point::Waypoint,
+            Point::Portal,
+            Point::Stairs,
+            Point::Obstacle { length: 0 },
+            Point::Obstacle { length: 3 },
+            Point::Terrain { cost: 1 },
+            // The last cost packed into the byte and the first ones kept aside
+            Point::Terrain { cost: 244 },
+            Point::Terrain { cost: 245 },
+            Point::Terrain { cost: 246 },
+            Point::Terrain { cost: u32::MAX },
+        ];
+        let packed = PackedPoints::new(points.into_iter());
+
+        assert!(packed.iter().eq(points));
+        assert_eq!(packed.terrain_costs.len(), 3);
+    }
+
+    #[test]
+    fn overwritten_terrain_is_not_kept_aside() {
+        let mut packed = PackedPoints::new([Point::Terrain { cost: 1000 }].into_iter());
+
+        packed.set(0, Point::Terrain { cost: 2 });
+
+        assert!(packed.get(0) == Point::Terrain { cost: 2 });
+        assert!(packed.terrain_costs.is_empty());
+    }
+
+    #[test]
+    fn chunks_are_allocated_on_write() {
+        let mut chunked = Chunked::<u32>::new(CHUNK_LEN * 3);
+        chunked.set(CHUNK_LEN + 1, 7);
+
+        assert_eq!(chunked.get(CHUNK_LEN + 1), 7);
+        assert_eq!(chunked.get(0), 0);
+        assert_eq!(chunked.chunks.iter().flatten().count(), 1);
+
+        chunked.clear();
+        assert_eq!(chunked.get(CHUNK_LEN + 1), 0);
+    }
+}
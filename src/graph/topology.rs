@@ -0,0 +1,142 @@
+use crate::graph::PointCoord;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HexOrientation {
+    // Rows of hexes, odd rows are shoved right ("odd-r" offset coordinates)
+    PointyTop,
+    // Columns of hexes, odd columns are shoved down ("odd-q" offset coordinates)
+    FlatTop,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    Square,
+    Hex(HexOrientation),
+}
+
+// Axial hex coordinates, the third cube coordinate is `-q - r`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AxialCoord {
+    pub q: i32,
+    pub r: i32,
+}
+
+impl Topology {
+    const SQUARE_DIRS: &[(i32, i32)] = &[(0, 1), (0, -1), (1, 0), (-1, 0)];
+    const HEX_DIRS: &[(i32, i32)] = &[(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+    // Coordinates of neighbors, they may lie outside of the graph
+    pub fn neighbor_coords(&self, point_coord: &PointCoord) -> [Option<(i32, i32)>; 6] {
+        let mut neighbor_coords = [None; 6];
+
+        match self {
+            Topology::Square => {
+                for (neighbor_coord, (di, dj)) in neighbor_coords.iter_mut().zip(Self::SQUARE_DIRS)
+                {
+                    *neighbor_coord = Some((point_coord.y as i32 + di, point_coord.x as i32 + dj));
+                }
+            }
+            Topology::Hex(orientation) => {
+                let axial = orientation.to_axial((point_coord.y as i32, point_coord.x as i32));
+
+                for (neighbor_coord, (dq, dr)) in neighbor_coords.iter_mut().zip(Self::HEX_DIRS) {
+                    *neighbor_coord = Some(orientation.to_offset(AxialCoord {
+                        q: axial.q + dq,
+                        r: axial.r + dr,
+                    }));
+                }
+            }
+        }
+
+        neighbor_coords
+    }
+
+    // Least number of moves between `a` and `b` on an empty graph
+    pub fn distance(&self, a: &PointCoord, b: &PointCoord) -> u32 {
+        self.offset_distance((a.y as i32, a.x as i32), (b.y as i32, b.x as i32))
+    }
+
+    // The same on an empty `n` x `m` graph wrapping around its edges, the way may cross them.
+    // Hex graphs wrap consistently only with an even number of shoved rows (columns).
+    pub fn wrapped_distance(&self, a: &PointCoord, b: &PointCoord, (n, m): (usize, usize)) -> u32 {
+        let (n, m) = (n as i32, m as i32);
+        let a = (a.y as i32, a.x as i32);
+
+        [-n, 0, n]
+            .into_iter()
+            .flat_map(|di| [-m, 0, m].map(|dj| (b.y as i32 + di, b.x as i32 + dj)))
+            .map(|b| self.offset_distance(a, b))
+            .min()
+            .unwrap()
+    }
+
+    // Distance between `(i, j)` offset coordinates, they may lie outside of the graph
+    fn offset_distance(&self, a: (i32, i32), b: (i32, i32)) -> u32 {
+        match self {
+            Topology::Square => a.0.abs_diff(b.0) + a.1.abs_diff(b.1),
+            Topology::Hex(orientation) => {
+                let a = orientation.to_axial(a);
+                let b = orientation.to_axial(b);
+
+                let (dq, dr) = (a.q - b.q, a.r - b.r);
+
+                (dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2
+            }
+        }
+    }
+
+    pub fn distance_name(&self, wrapped: bool) -> &'static str {
+        match (self, wrapped) {
+            (Topology::Square, false) => "Manhattan distance",
+            (Topology::Square, true) => "Wrapped Manhattan distance",
+            (Topology::Hex(_), false) => "Hex distance",
+            (Topology::Hex(_), true) => "Wrapped hex distance",
+        }
+    }
+
+    pub fn next(&self) -> Topology {
+        match self {
+            Topology::Square => Topology::Hex(HexOrientation::PointyTop),
+            Topology::Hex(HexOrientation::PointyTop) => Topology::Hex(HexOrientation::FlatTop),
+            Topology::Hex(HexOrientation::FlatTop) => Topology::Square,
+        }
+    }
+}
+
+impl HexOrientation {
+    // From offset coordinates `(y, x)`
+    pub fn to_axial(self, (y, x): (i32, i32)) -> AxialCoord {
+        match self {
+            HexOrientation::PointyTop => AxialCoord {
+                q: x - (y - (y & 1)) / 2,
+                r: y,
+            },
+            HexOrientation::FlatTop => AxialCoord {
+                q: x,
+                r: y - (x - (x & 1)) / 2,
+            },
+        }
+    }
+
+    // Offset coordinates as `(y, x)`
+    pub fn to_offset(self, axial: AxialCoord) -> (i32, i32) {
+        match self {
+            HexOrientation::PointyTop => (axial.r, axial.q + (axial.r - (axial.r & 1)) / 2),
+            HexOrientation::FlatTop => (axial.r + (axial.q - (axial.q & 1)) / 2, axial.q),
+        }
+    }
+}
+
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Topology::Square => "Square",
+                Topology::Hex(HexOrientation::PointyTop) => "Hex[Pointy top]",
+                Topology::Hex(HexOrientation::FlatTop) => "Hex[Flat top]",
+            },
+        )
+    }
+}
@@ -1,131 +1,2217 @@
+mod camera;
+mod editor;
+mod font;
+mod shapes;
+
 use pixels::{Pixels, SurfaceTexture};
 use winit::dpi::LogicalSize;
 use winit::event::{Event, VirtualKeyCode};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
+use winit::window::{Window, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
-use anyhow::Context;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+
+use crate::algo::{FrontierNode, PathFinder};
+use crate::graph::{
+    AxialCoord, EventLogger, Generation, GraphPath, HexOrientation, Mark, Network, Point,
+    PointCoord, SearchGraph, SearchMetrics, Topology,
+};
+use crate::{algo, graph};
+use camera::Camera;
+use editor::Editor;
+
+// Initial and minimal size of the window
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+const SQRT_3: f32 = 1.732_050_8;
+// History keeps every step, a search not completing by then is stopped
+const MAX_RECORDED_STEPS: usize = 1_000_000;
+
+// Part of the window a grid is drawn in
+#[derive(Clone, Copy)]
+struct Viewport {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+// Cells of the markers drawn over the grid, found once a frame and not for every pixel
+struct Markers {
+    // Of the shown alternative path, without its start and end
+    alternative: HashSet<PointCoord>,
+    agent: Option<PointCoord>,
+    // Open or closed at the current tick
+    doors: HashMap<PointCoord, bool>,
+}
+
+// Algorithm compared with the shown one on the same graph, stepped in lockstep with it
+struct Compared {
+    algo: Box<dyn PathFinder>,
+    metrics: Rc<RefCell<SearchMetrics>>,
+}
+
+// Algorithm drawn in its viewport with the layout and the camera of the world
+struct Grid<'a> {
+    world: &'a World,
+    algo: &'a dyn PathFinder,
+    viewport: Viewport,
+    // Shown layers from the left, found once a frame and not for every pixel
+    layers: Vec<usize>,
+}
+
+struct World {
+    // Size of the pixel buffer, follows the size of the window
+    width: u32,
+    height: u32,
+    algo: Box<dyn PathFinder>,
+    // Makes the shown algorithm again, e.g. to compare it with the others
+    new_algo: fn(Arc<graph::Graph>) -> Box<dyn PathFinder>,
+    // Drawn in their own viewports next to the shown algorithm
+    compared: Vec<Compared>,
+    // Zoomed and panned part of the layers shown in the viewports
+    camera: Camera,
+    // Kept when another graph is set
+    topology: Topology,
+    wrap: bool,
+    stairs_length: u32,
+    // Shown layer of a multi-level graph, unless all of them are shown side by side
+    layer: usize,
+    side_by_side: bool,
+    // Top of the terrain color gradient
+    max_terrain_cost: u32,
+    // Pixel under the mouse cursor
+    hovered: Option<(u32, u32)>,
+    // Shown instead of the grid when set
+    network: Option<Box<dyn PathFinder<Network>>>,
+
+    // Time axis position of a timed path
+    tick: u32,
+    // The time axis moves on by itself
+    playing: bool,
+    next_tick: Instant,
+    // Steps of the search are made by themselves
+    auto_play: bool,
+    steps_per_second: u32,
+    next_step: Instant,
+    // Shown one of the alternative paths
+    alternative: usize,
+    // `g`, `h` and `f` are written on the reached cells
+    show_costs: bool,
+    // Stats and legend along the right border, key help over everything
+    show_hud: bool,
+    show_help: bool,
+    // Name of the shown map
+    map: String,
+    // Cell shown in the inspector
+    inspected: Option<PointCoord>,
+    // Mouse paints the map instead of inspecting cells when enabled
+    editor: Editor,
+
+    // Collect the events of the shown search
+    metrics: Rc<RefCell<SearchMetrics>>,
+    event_logger: Rc<RefCell<EventLogger>>,
+}
+
+impl World {
+    const SEEN_CELL_BORDER: u32 = 10;
+    const WAYPOINT_ORDER_SCALE: u32 = 3;
+    const NODE_RADIUS: i32 = 7;
+    const WALL_WIDTH: u32 = 4;
+    const GRID_MIN_CELL_RADIUS: u32 = 3;
+    const MINIMAP_SIZE: u32 = 120;
+    const MINIMAP_MARGIN: u32 = 12;
+    // Half of the arrow as a part of the distance between the centers of neighbors
+    const ARROW_LENGTH: f32 = 0.3;
+    const ARROW_RADIUS: i32 = 1;
+    const WRAPPED_STEP_RADIUS: i32 = 3;
+    const STAIRS_ARROW_LENGTH: i32 = 10;
+    // Arrows up and down are drawn side by side
+    const STAIRS_ARROW_OFFSET: i32 = 4;
+    const TOOLTIP_SCALE: u32 = 2;
+    const TOOLTIP_PADDING: u32 = 3;
+    // From the mouse cursor to the tooltip's corner
+    const TOOLTIP_OFFSET: u32 = 12;
+    const TERRAIN_LEVELS: usize = 16;
+    const MOVING_OBSTACLE_RADIUS: i32 = 8;
+    // Bar along the bottom border showing the applied part of the recorded steps
+    const TIMELINE_HEIGHT: u32 = 8;
+    const COST_SCALE: u32 = 2;
+    const COST_SPACING: u32 = 2;
+    // Between the columns of the comparison table
+    const COMPARISON_SPACING: u32 = 24;
+    const HUD_WIDTH: u32 = 220;
+    // Between the keys and their descriptions in the help
+    const HELP_SPACING: u32 = 12;
+    const TICK_DURATION: Duration = Duration::from_millis(400);
+    // Viewports of the compared algorithms besides the shown one
+    const MAX_COMPARED: usize = 5;
+    // Faster auto-play makes several steps a frame
+    const FRAMES_PER_SECOND: u32 = 60;
+    const STEPS_PER_SECOND: u32 = 8;
+    const MAX_STEPS_PER_SECOND: u32 = 3840;
+
+    const GRID_COLOR: &[u8] = &[0x5e, 0x48, 0xe8, 0xff];
+    const BACKGROUND_COLOR: &[u8] = &[0x18, 0x18, 0x18, 0xff];
+    const START_COLOR: &[u8] = &[0xff, 0x00, 0x00, 0xff];
+    const END_COLOR: &[u8] = &[0xd7, 0x42, 0xf5, 0xff];
+    const PATH_COLOR: &[u8] = &[0xff, 0xb0, 0x00, 0xff];
+    const OBSTACLE_COLOR: [&[u8]; 4] = [
+        &[0x60, 0xbf, 0x74, 0xff],
+        &[0x40, 0xbf, 0x74, 0xbf],
+        &[0x20, 0xbf, 0x74, 0x80],
+        &[0x00, 0xbf, 0x74, 0x40],
+    ];
+    const SEEN_COLOR: &[u8] = &[0xff, 0xff, 0x91, 0xff];
+    const PRUNED_COLOR: &[u8] = &[0x8c, 0x8c, 0x8c, 0xff];
+    const FRONTIER_COLOR: &[u8] = &[0x91, 0xd5, 0xff, 0xff];
+    const NEXT_FRONTIER_COLOR: &[u8] = &[0x3d, 0x7e, 0xff, 0xff];
+    const AGENT_COLOR: &[u8] = &[0x00, 0x9d, 0xff, 0xff];
+    const RESERVED_COLOR: &[u8] = &[0xff, 0x5c, 0x5c, 0xff];
+    const WAYPOINT_COLOR: &[u8] = &[0xf0, 0xf0, 0xf0, 0xff];
+    const WALL_COLOR: &[u8] = &[0xe8, 0xe8, 0xe8, 0xff];
+    const PORTAL_COLOR: &[u8] = &[0x00, 0xe5, 0xff, 0xff];
+    const STAIRS_COLOR: &[u8] = &[0xa0, 0x6e, 0x3c, 0xff];
+    const DOOR_OPEN_COLOR: &[u8] = &[0x4c, 0x6b, 0x8a, 0xff];
+    const DOOR_CLOSED_COLOR: &[u8] = &[0x8a, 0x2b, 0x2b, 0xff];
+    const MOVING_OBSTACLE_COLOR: &[u8] = &[0xff, 0x6b, 0x1a, 0xff];
+    const ARROW_COLOR: &[u8] = &[0x9e, 0x8c, 0xff, 0xff];
+    // From the cheapest to the most expensive terrain
+    const TERRAIN_COLORS: [[u8; 4]; Self::TERRAIN_LEVELS] =
+        Self::gradient([0x1f, 0x3d, 0x2b], [0xc8, 0x9b, 0x5a]);
+    const LEGEND: [(&[u8], &str); 10] = [
+        (Self::START_COLOR, "Start"),
+        (Self::END_COLOR, "End"),
+        (Self::SEEN_COLOR, "Seen"),
+        (Self::FRONTIER_COLOR, "Frontier"),
+        (Self::NEXT_FRONTIER_COLOR, "Expanded next"),
+        (Self::PATH_COLOR, "Path"),
+        (Self::OBSTACLE_COLOR[0], "Obstacle 0"),
+        (Self::OBSTACLE_COLOR[1], "Obstacle 1"),
+        (Self::OBSTACLE_COLOR[2], "Obstacle 2"),
+        (Self::OBSTACLE_COLOR[3], "Obstacle 3"),
+    ];
+    const KEY_HELP: [(&str, &str); 52] = [
+        ("S", "Make (replay) a step"),
+        ("Shift+S", "Run to completion"),
+        ("Space", "Auto-play (pause) steps"),
+        ("PgUp/PgDn", "Speed auto-play up (down)"),
+        ("Z", "Step back"),
+        ("Home/End", "Jump to the first (last) step"),
+        ("Timeline", "Click to jump to a step"),
+        ("R", "Reset the search"),
+        ("C", "Show g, h, f of reached cells"),
+        ("Click", "Inspect a cell"),
+        ("Wheel", "Zoom in (out) at the cursor"),
+        ("Middle drag", "Pan, right drag too out of editor"),
+        ("Backspace", "Reset zoom and pan"),
+        ("V", "Print every event"),
+        ("Enter", "Switch map editor"),
+        ("Left drag", "Paint obstacles, move starts, ends"),
+        ("Right drag", "Erase obstacles and terrain"),
+        ("J", "Switch painted obstacle difficulty"),
+        ("Ctrl+Z/Y", "Undo (redo) an edit"),
+        ("Ctrl+S", "Save map to map.txt"),
+        ("Ctrl+O", "Load map from map.txt"),
+        ("D", "Dijkstra"),
+        ("B", "BFS"),
+        ("H", "Heuristic"),
+        ("A", "A*"),
+        ("T", "Space-time A*"),
+        ("I", "Time-dependent A*"),
+        ("U", "Time-dependent Dijkstra"),
+        ("Y", "K shortest paths (Yen's)"),
+        ("W", "Waypoints"),
+        ("E", "Beam search"),
+        ("M", "Frontier bounded A*"),
+        ("\\", "Compare with the ones set next"),
+        ("Delete", "Stop comparing"),
+        ("X", "Switch grid topology"),
+        ("O", "Switch wrapping around edges"),
+        ("F", "Show next layer"),
+        ("G", "Show layers side by side"),
+        ("K/L", "Shorter (longer) stairs"),
+        ("[ / ]", "Time axis tick back (forward)"),
+        ("P", "Play (pause) time axis"),
+        ("Tab", "Show next alternative path"),
+        (", / .", "Smaller (greater) bound"),
+        ("1-7", "Predefined graphs"),
+        ("8", "Random terrain"),
+        ("9", "Door, moving obstacle, rush hours"),
+        ("-", "Random graph"),
+        ("=", "Random graph with waypoints"),
+        ("N", "Random road network"),
+        ("F1", "Show (hide) this help"),
+        ("F2", "Show (hide) HUD"),
+        ("Q", "Quit"),
+    ];
+    const ALTERNATIVE_COLOR: [&[u8]; 4] = [
+        &[0xff, 0xb0, 0x00, 0xff],
+        &[0x00, 0xd5, 0xff, 0xff],
+        &[0xff, 0x4f, 0xd8, 0xff],
+        &[0x9d, 0xff, 0x00, 0xff],
+    ];
+}
+
+impl World {
+    const fn gradient(from: [u8; 3], to: [u8; 3]) -> [[u8; 4]; Self::TERRAIN_LEVELS] {
+        let last = Self::TERRAIN_LEVELS - 1;
+        let mut colors = [[0xff; 4]; Self::TERRAIN_LEVELS];
+
+        let mut level = 0;
+        while level <= last {
+            let mut channel = 0;
+            while channel < 3 {
+                let (from, to) = (from[channel] as usize, to[channel] as usize);
+                colors[level][channel] = ((from * (last - level) + to * level) / last) as u8;
+                channel += 1;
+            }
+            level += 1;
+        }
+
+        colors
+    }
+
+    fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let graph = graph::Graph::generate_graph(Generation::Predefined1);
+        let max_terrain_cost = graph.max_terrain_cost();
+
+        let algo = algo::Dijkstra::new(Arc::new(graph));
+
+        let mut world = Self {
+            width,
+            height,
+            algo,
+            new_algo: algo::Dijkstra::new,
+            compared: vec![],
+            camera: Camera::new(),
+            topology: Topology::Square,
+            wrap: false,
+            stairs_length: graph::Graph::STAIRS_LENGTH,
+            layer: 0,
+            side_by_side: false,
+            max_terrain_cost,
+            hovered: None,
+            network: None,
+            tick: 0,
+            playing: false,
+            next_tick: Instant::now(),
+            auto_play: false,
+            steps_per_second: Self::STEPS_PER_SECOND,
+            next_step: Instant::now(),
+            alternative: 0,
+            show_costs: false,
+            show_hud: true,
+            show_help: false,
+            map: Generation::Predefined1.to_string(),
+            inspected: None,
+            editor: Editor::new(),
+            metrics: Rc::new(RefCell::new(SearchMetrics::default())),
+            event_logger: Rc::new(RefCell::new(EventLogger::default())),
+        };
+        world.observe();
+
+        Ok(world)
+    }
+
+    // Subscribes the metrics and the event logger to the newly shown search
+    fn observe(&mut self) {
+        *self.metrics.borrow_mut() = SearchMetrics::default();
+
+        match &mut self.network {
+            Some(network) => {
+                network.subscribe(Box::new(Rc::clone(&self.metrics)));
+                network.subscribe(Box::new(Rc::clone(&self.event_logger)));
+            }
+            None => {
+                self.algo.subscribe(Box::new(Rc::clone(&self.metrics)));
+                self.algo.subscribe(Box::new(Rc::clone(&self.event_logger)));
+            }
+        }
+    }
+
+    fn color_by_point(&self, point: Point) -> &'static [u8] {
+        match point {
+            Point::Start => Self::START_COLOR,
+            Point::End => Self::END_COLOR,
+            Point::Free => Self::BACKGROUND_COLOR,
+            Point::Waypoint => Self::WAYPOINT_COLOR,
+            Point::Portal => Self::PORTAL_COLOR,
+            Point::Stairs => Self::STAIRS_COLOR,
+            Point::Terrain { cost } => self.terrain_color(cost),
+            Point::Obstacle { length } => {
+                assert!(length < 4);
+                Self::OBSTACLE_COLOR[length as usize]
+            }
+        }
+    }
+
+    fn terrain_color(&self, cost: u32) -> &'static [u8] {
+        let level = (cost - 1) as usize * (Self::TERRAIN_LEVELS - 1)
+            / (self.max_terrain_cost - 1).max(1) as usize;
+
+        &Self::TERRAIN_COLORS[level.min(Self::TERRAIN_LEVELS - 1)]
+    }
+
+    // The node expanded next is highlighted
+    fn frontier_color(order: usize) -> &'static [u8] {
+        if order == 0 {
+            Self::NEXT_FRONTIER_COLOR
+        } else {
+            Self::FRONTIER_COLOR
+        }
+    }
+
+    fn color_by_mark(mark: Mark) -> Option<&'static [u8]> {
+        match mark {
+            Mark::Unseen => None,
+            Mark::Seen => Some(Self::SEEN_COLOR),
+            Mark::Pruned => Some(Self::PRUNED_COLOR),
+            Mark::Path => Some(Self::PATH_COLOR),
+        }
+    }
+
+    // Zooms in (out) keeping the layer's pixel under the cursor in place
+    fn zoom_at(&mut self, (pixel_x, pixel_y): (u32, u32), steps: f32) {
+        let viewport = self.viewport();
+        if !(viewport.x..viewport.x + viewport.width).contains(&pixel_x)
+            || !(viewport.y..viewport.y + viewport.height).contains(&pixel_y)
+        {
+            return;
+        }
+
+        // Relative to the view of the layer under the cursor
+        let view_width = self.grid().view_width();
+        let cursor = (
+            ((pixel_x - viewport.x) % view_width) as f32,
+            (pixel_y - viewport.y) as f32,
+        );
+
+        let (layer_width, layer_height) = self.grid().layer_size();
+        let (offset_x, offset_y) = self
+            .camera
+            .offset(self.grid().layer_size(), self.grid().view_size());
+        let (part_x, part_y) = (
+            (offset_x + cursor.0) / layer_width,
+            (offset_y + cursor.1) / layer_height,
+        );
+
+        if !self.camera.zoom_by(steps) {
+            return;
+        }
+
+        let (layer_width, layer_height) = self.grid().layer_size();
+        self.camera.set_offset(
+            (
+                part_x * layer_width - cursor.0,
+                part_y * layer_height - cursor.1,
+            ),
+            (layer_width, layer_height),
+            self.grid().view_size(),
+        );
+    }
+
+    // Moves the shown part of the layers along with the dragging cursor
+    fn pan(&mut self, (dx, dy): (f32, f32)) {
+        let (offset_x, offset_y) = self
+            .camera
+            .offset(self.grid().layer_size(), self.grid().view_size());
+
+        self.camera.set_offset(
+            (offset_x - dx, offset_y - dy),
+            self.grid().layer_size(),
+            self.grid().view_size(),
+        );
+    }
+
+    fn node_color(
+        network: &dyn PathFinder<Network>,
+        node: usize,
+        frontier_order: &HashMap<usize, usize>,
+    ) -> &'static [u8] {
+        if network.graph_wrapper().is_start(&node) {
+            return Self::START_COLOR;
+        }
+        if network.graph_wrapper().is_end(&node) {
+            return Self::END_COLOR;
+        }
+
+        let mark = network.graph_wrapper().mark(&node);
+        if let Some(&order) = frontier_order.get(&node).filter(|_| mark != Mark::Path) {
+            return Self::frontier_color(order);
+        }
+
+        match mark {
+            Mark::Unseen => Self::WAYPOINT_COLOR,
+            Mark::Seen => Self::SEEN_COLOR,
+            Mark::Pruned => Self::PRUNED_COLOR,
+            Mark::Path => Self::PATH_COLOR,
+        }
+    }
+
+    // Nodes as discs, edges as lines between them, the found path is highlighted.
+    // The nodes are spread over `width` from the left border keeping their proportions.
+    fn draw_network(&self, frame: &mut [u8], network: &dyn PathFinder<Network>, width: u32) {
+        for pixel in frame.chunks_exact_mut(4) {
+            pixel.copy_from_slice(Self::BACKGROUND_COLOR);
+        }
+
+        let margin = (Self::NODE_RADIUS * 2) as f32;
+        let (width, height) = (
+            width as f32 - 2.0 * margin,
+            self.height as f32 - 2.0 * margin,
+        );
+        let scale = (width / Network::WIDTH).min(height / Network::HEIGHT);
+        // Letterboxed along the longer side
+        let (left, top) = (
+            margin + (width - Network::WIDTH * scale) / 2.0,
+            margin + (height - Network::HEIGHT * scale) / 2.0,
+        );
+        let position = |node: usize| {
+            network
+                .graph()
+                .coords(&node)
+                .map(|(x, y)| ((left + x * scale) as i32, (top + y * scale) as i32))
+        };
+
+        let graph_wrapper = network.graph_wrapper();
+        let on_path = |node: usize| {
+            graph_wrapper.is_completed()
+                && (graph_wrapper.mark(&node) == Mark::Path
+                    || graph_wrapper.is_start(&node)
+                    || graph_wrapper.is_end(&node))
+        };
+
+        // Nodes without coordinates are not drawn
+        for from in 0..network.graph().node_count() {
+            for &(to, _) in network.graph().adjacency(from) {
+                let (Some(from_position), Some(to_position)) = (position(from), position(to))
+                else {
+                    continue;
+                };
+
+                let is_path_edge = on_path(from)
+                    && on_path(to)
+                    && matches!(graph_wrapper.came_from(&to), Some((parent, _)) if parent == from);
+
+                let color = if is_path_edge {
+                    Self::PATH_COLOR
+                } else {
+                    Self::GRID_COLOR
+                };
+                shapes::draw_line(frame, self.width, from_position, to_position, color);
+            }
+        }
+
+        let frontier_order = frontier_order(&shown_frontier(network));
+        for node in 0..network.graph().node_count() {
+            if let Some(node_position) = position(node) {
+                shapes::draw_disc(
+                    frame,
+                    self.width,
+                    node_position,
+                    Self::NODE_RADIUS,
+                    Self::node_color(network, node, &frontier_order),
+                );
+            }
+        }
+    }
+
+    // The timeline lies over the HUD
+    fn draw(&self, frame: &mut [u8]) {
+        if let Some(network) = &self.network {
+            self.draw_network(frame, network.as_ref(), self.grid_viewport().width);
+            if self.show_hud {
+                self.draw_hud(frame);
+            }
+            self.draw_timeline(frame, network.graph_wrapper().timeline());
+        } else {
+            self.draw_grids(frame);
+            if self.show_hud {
+                self.draw_hud(frame);
+            }
+
+            self.draw_timeline(frame, self.algo.graph_wrapper().timeline());
+            self.draw_inspector(frame);
+            self.draw_tooltip(frame);
+        }
+
+        if self.show_help {
+            self.draw_help(frame);
+        }
+    }
+
+    // The shown algorithm, the compared ones with the comparison table below them
+    fn draw_grids(&self, frame: &mut [u8]) {
+        if self.compared.is_empty() {
+            self.grid().draw_grid(frame);
+        } else {
+            for pixel in frame.chunks_exact_mut(4) {
+                pixel.copy_from_slice(Self::BACKGROUND_COLOR);
+            }
+
+            // Every compared algorithm is drawn as the shown one in its own viewport
+            let algos = std::iter::once(self.algo.as_ref())
+                .chain(self.compared.iter().map(|compared| compared.algo.as_ref()));
+            for (algo, viewport) in algos.zip(self.viewports()) {
+                let grid = Grid::new(self, algo, viewport);
+                grid.draw_grid(frame);
+                grid.draw_name(frame);
+            }
+
+            self.draw_comparison(frame);
+        }
+    }
+
+    // The layout follows the new size of the pixel buffer
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    // Window without the HUD, the grids are drawn in it
+    fn grid_viewport(&self) -> Viewport {
+        let hud_width = if self.show_hud { Self::HUD_WIDTH } else { 0 };
+
+        Viewport {
+            x: 0,
+            y: 0,
+            width: self.width - hud_width,
+            height: self.height,
+        }
+    }
+
+    // Name, heuristic and progress of the shown search on the map with the legend of the colors
+    // along the right border
+    fn draw_hud(&self, frame: &mut [u8]) {
+        let (name, heuristic, map, numbers) = match &self.network {
+            Some(network) => (
+                network.name(),
+                network.heuristic_name(),
+                "Road network",
+                search_numbers(network.as_ref()),
+            ),
+            None => (
+                self.algo.name(),
+                self.algo.heuristic_name(),
+                self.map.as_str(),
+                search_numbers(self.algo.as_ref()),
+            ),
+        };
+
+        let (x, height) = (self.width - Self::HUD_WIDTH, self.height);
+        shapes::fill_rect(
+            frame,
+            self.width,
+            (x as i32, 0),
+            (Self::HUD_WIDTH as i32, height as i32),
+            Self::BACKGROUND_COLOR,
+        );
+        shapes::fill_rect(
+            frame,
+            self.width,
+            (x as i32, 0),
+            (1, height as i32),
+            Self::GRID_COLOR,
+        );
+
+        let (left, right) = (
+            x + Self::TOOLTIP_PADDING * 2,
+            self.width - Self::TOOLTIP_PADDING * 2,
+        );
+        let line_step = Self::line_step();
+        let mut y = Self::TOOLTIP_PADDING * 2;
+        let mut draw_text = |x: u32, y: u32, text: &str, color: &[u8]| {
+            font::draw_text(frame, self.width, (x, y), Self::TOOLTIP_SCALE, text, color);
+        };
+
+        // Names below their headings, numbers to the right of theirs
+        for (heading, text) in [("Algorithm", name), ("Heuristic", heuristic), ("Map", map)] {
+            draw_text(left, y, heading, Self::GRID_COLOR);
+            draw_text(left, y + line_step, text, Self::WAYPOINT_COLOR);
+            y += line_step * 2;
+        }
+        y += line_step;
+        for (heading, number) in &numbers {
+            let (number_width, _) = font::text_size(Self::TOOLTIP_SCALE, number);
+
+            draw_text(left, y, heading, Self::GRID_COLOR);
+            draw_text(right - number_width, y, number, Self::WAYPOINT_COLOR);
+            y += line_step;
+        }
+        y += line_step;
+
+        // Lengths of the alternatives in their colors, the one Tab shows is highlighted
+        let alternative_paths = if self.network.is_some() || self.grid().is_rewound() {
+            vec![]
+        } else {
+            self.algo.alternative_paths()
+        };
+        if let Some(shortest) = alternative_paths.first() {
+            let paths = alternative_paths.len().to_string();
+            let (paths_width, _) = font::text_size(Self::TOOLTIP_SCALE, &paths);
+            draw_text(left, y, "Paths", Self::GRID_COLOR);
+            draw_text(right - paths_width, y, &paths, Self::WAYPOINT_COLOR);
+            y += line_step;
+
+            for (i, alternative) in alternative_paths.iter().enumerate() {
+                let heading = format!("Path {}", i + 1);
+                let length = match alternative.length() - shortest.length() {
+                    0 => alternative.length().to_string(),
+                    longer => format!("{} (+{longer})", alternative.length()),
+                };
+                let (length_width, _) = font::text_size(Self::TOOLTIP_SCALE, &length);
+                let heading_color = if i == self.alternative {
+                    Self::WAYPOINT_COLOR
+                } else {
+                    Self::GRID_COLOR
+                };
+                let color = Self::ALTERNATIVE_COLOR[i % Self::ALTERNATIVE_COLOR.len()];
+
+                draw_text(left, y, &heading, heading_color);
+                draw_text(right - length_width, y, &length, color);
+                y += line_step;
+            }
+            y += line_step;
+        }
+
+        draw_text(left, y, "Legend", Self::GRID_COLOR);
+        y += line_step;
+        let (_, swatch) = font::text_size(Self::TOOLTIP_SCALE, "");
+        for (color, text) in Self::LEGEND {
+            shapes::fill_rect(
+                frame,
+                self.width,
+                (left as i32, y as i32),
+                (swatch as i32, swatch as i32),
+                color,
+            );
+            font::draw_text(
+                frame,
+                self.width,
+                (left + swatch * 2, y),
+                Self::TOOLTIP_SCALE,
+                text,
+                Self::WAYPOINT_COLOR,
+            );
+            y += line_step;
+        }
+        y += line_step;
+
+        font::draw_text(
+            frame,
+            self.width,
+            (left, y),
+            Self::TOOLTIP_SCALE,
+            "F1 - keys",
+            Self::GRID_COLOR,
+        );
+    }
+
+    // Every key binding over the whole window in as many columns as fit into it,
+    // in smaller text if they don't fit at full size
+    fn draw_help(&self, frame: &mut [u8]) {
+        for pixel in frame.chunks_exact_mut(4) {
+            pixel.copy_from_slice(Self::BACKGROUND_COLOR);
+        }
+
+        let padding = Self::TOOLTIP_PADDING * 2;
+        let layout = |scale: u32| {
+            let width = |text: &str| font::text_size(scale, text).0;
+            let key_width = Self::KEY_HELP.iter().map(|(key, _)| width(key)).max();
+            let description_width = Self::KEY_HELP.iter().map(|(_, text)| width(text)).max();
+            let key_width = key_width.unwrap() + Self::HELP_SPACING;
+            let column_width = key_width + description_width.unwrap() + Self::HELP_SPACING;
+
+            let columns = ((self.width - padding) / column_width).max(1) as usize;
+            let rows = Self::KEY_HELP.len().div_ceil(columns);
+            let line_step = font::text_size(scale, "").1 + Self::COST_SPACING * 2;
+
+            (scale, key_width, column_width, rows, line_step)
+        };
+        let full = layout(Self::TOOLTIP_SCALE);
+        let (_, _, _, rows, line_step) = full;
+        let (scale, key_width, column_width, rows, line_step) =
+            if padding * 2 + (rows as u32 + 2) * line_step <= self.height {
+                full
+            } else {
+                layout(1)
+            };
+
+        font::draw_text(
+            frame,
+            self.width,
+            (padding, padding),
+            scale,
+            "Keys",
+            Self::GRID_COLOR,
+        );
+        for (i, (key, description)) in Self::KEY_HELP.iter().enumerate() {
+            let x = padding + (i / rows) as u32 * column_width;
+            let y = padding + ((i % rows) as u32 + 2) * line_step;
+
+            font::draw_text(frame, self.width, (x, y), scale, key, Self::SEEN_COLOR);
+            font::draw_text(
+                frame,
+                self.width,
+                (x + key_width, y),
+                scale,
+                description,
+                Self::WAYPOINT_COLOR,
+            );
+        }
+    }
+
+    // Part of the window the grid of the shown algorithm is drawn in
+    fn viewport(&self) -> Viewport {
+        self.viewports()[0]
+    }
+
+    // Shown algorithm in its viewport, the mouse works on it
+    fn grid(&self) -> Grid<'_> {
+        Grid::new(self, self.algo.as_ref(), self.viewport())
+    }
+
+    // Viewports of the shown algorithm and the compared ones row by row,
+    // the comparison table lies below them
+    fn viewports(&self) -> Vec<Viewport> {
+        let count = self.compared.len() as u32 + 1;
+        let columns = (1..).find(|columns| columns * columns >= count).unwrap();
+        let rows = count.div_ceil(columns);
+
+        let width = self.grid_viewport().width / columns;
+        let height = (self.height - self.comparison_height()) / rows;
+
+        (0..count)
+            .map(|i| Viewport {
+                x: i % columns * width,
+                y: i / columns * height,
+                width,
+                height,
+            })
+            .collect()
+    }
+
+    fn line_step() -> u32 {
+        let (_, line_height) = font::text_size(Self::TOOLTIP_SCALE, "");
+
+        line_height + Self::COST_SPACING * 2
+    }
+
+    // Heading and a row of every algorithm above the timeline, nothing without compared ones
+    fn comparison_height(&self) -> u32 {
+        if self.compared.is_empty() {
+            return 0;
+        }
+
+        let rows = self.compared.len() as u32 + 2;
+        rows * Self::line_step() + Self::TOOLTIP_PADDING * 2 + Self::TIMELINE_HEIGHT
+    }
+
+    // Seen points, path length and steps taken of every algorithm in the viewports' order
+    fn draw_comparison(&self, frame: &mut [u8]) {
+        let mut rows = vec![["Algorithm", "Seen", "Length", "Steps"].map(String::from)];
+        let algos = std::iter::once(&self.algo).chain(self.compared.iter().map(|c| &c.algo));
+        for algo in algos {
+            let graph_wrapper = algo.graph_wrapper();
+
+            rows.push([
+                algo.name().to_string(),
+                graph_wrapper.seen_points.to_string(),
+                graph_wrapper
+                    .path_length()
+                    .map_or("-".to_string(), |length| length.to_string()),
+                graph_wrapper.timeline().0.to_string(),
+            ]);
+        }
+
+        let mut column_x = Self::TOOLTIP_PADDING;
+        let top = self.height - self.comparison_height() + Self::TOOLTIP_PADDING;
+        for column in 0..4 {
+            for (i, row) in rows.iter().enumerate() {
+                let color = if i == 0 {
+                    Self::GRID_COLOR
+                } else {
+                    Self::WAYPOINT_COLOR
+                };
+
+                font::draw_text(
+                    frame,
+                    self.width,
+                    (column_x, top + i as u32 * Self::line_step()),
+                    Self::TOOLTIP_SCALE,
+                    &row[column],
+                    color,
+                );
+            }
+
+            let column_width = rows
+                .iter()
+                .map(|row| font::text_size(Self::TOOLTIP_SCALE, &row[column]).0)
+                .max()
+                .unwrap();
+            column_x += column_width + Self::COMPARISON_SPACING;
+        }
+    }
+
+    // Predecessor, costs and steps of the inspected cell in the top corner away from it,
+    // the predecessor is connected to it
+    fn draw_inspector(&self, frame: &mut [u8]) {
+        let Some(point_coord) = &self.inspected else {
+            return;
+        };
+        let graph_wrapper = self.algo.graph_wrapper();
+
+        let mut lines = vec![format!("Cell {point_coord}")];
+        match graph_wrapper.came_from(point_coord) {
+            Some((parent, g)) => {
+                let h = self.algo.heuristic(point_coord);
+
+                if &parent == point_coord {
+                    lines.push("From start".to_string());
+                } else {
+                    lines.push(format!("From {parent}"));
+
+                    if let (Some((x, y)), Some((parent_x, parent_y))) = (
+                        self.grid().cell_center(point_coord),
+                        self.grid().cell_center(&parent),
+                    ) {
+                        shapes::draw_line(
+                            frame,
+                            self.width,
+                            (parent_x as i32, parent_y as i32),
+                            (x as i32, y as i32),
+                            Self::WAYPOINT_COLOR,
+                        );
+                    }
+                }
+                lines.push(format!("G {g}  H {h}  F {}", g + h));
+            }
+            None => lines.push("No predecessor".to_string()),
+        }
+
+        let (discovered, expanded) = graph_wrapper.discovered_and_expanded(point_coord);
+        if let Some(discovered) = discovered {
+            lines.push(format!("Discovered at step {discovered}"));
+        }
+        lines.push(match expanded {
+            Some(expanded) => format!("Expanded at step {expanded}"),
+            None => "Not expanded".to_string(),
+        });
+
+        let line_step = Self::line_step();
+        let text_width = lines
+            .iter()
+            .map(|line| font::text_size(Self::TOOLTIP_SCALE, line).0)
+            .max()
+            .unwrap();
+        let (width, height) = (
+            text_width + Self::TOOLTIP_PADDING * 2,
+            line_step * lines.len() as u32 - Self::COST_SPACING * 2 + Self::TOOLTIP_PADDING * 2,
+        );
+
+        let grid_width = self.grid_viewport().width;
+        let is_right = self
+            .grid()
+            .cell_center(point_coord)
+            .is_some_and(|(x, _)| x >= grid_width / 2);
+        let x = if is_right { 0 } else { grid_width - width };
+
+        shapes::fill_rect(
+            frame,
+            self.width,
+            (x as i32, 0),
+            (width as i32, height as i32),
+            Self::GRID_COLOR,
+        );
+        for (i, line) in lines.iter().enumerate() {
+            font::draw_text(
+                frame,
+                self.width,
+                (
+                    x + Self::TOOLTIP_PADDING,
+                    Self::TOOLTIP_PADDING + i as u32 * line_step,
+                ),
+                Self::TOOLTIP_SCALE,
+                line,
+                Self::WAYPOINT_COLOR,
+            );
+        }
+    }
+
+    // Applied part of the recorded steps, nothing before the first step
+    fn draw_timeline(&self, frame: &mut [u8], (applied, recorded): (usize, usize)) {
+        if recorded == 0 {
+            return;
+        }
+
+        let top = (self.height - Self::TIMELINE_HEIGHT) as i32;
+        let applied_width = (self.width as usize * applied / recorded) as i32;
+
+        shapes::fill_rect(
+            frame,
+            self.width,
+            (0, top),
+            (self.width as i32, Self::TIMELINE_HEIGHT as i32),
+            Self::GRID_COLOR,
+        );
+        shapes::fill_rect(
+            frame,
+            self.width,
+            (0, top),
+            (applied_width, Self::TIMELINE_HEIGHT as i32),
+            Self::SEEN_COLOR,
+        );
+    }
+
+    // Step of the timeline under the pixel, `None` off the timeline
+    fn timeline_step_at(&self, (pixel_x, pixel_y): (u32, u32)) -> Option<usize> {
+        let recorded = match &self.network {
+            Some(network) => network.graph_wrapper().timeline().1,
+            None => self.algo.graph_wrapper().timeline().1,
+        };
+        if recorded == 0 || pixel_y < self.height - Self::TIMELINE_HEIGHT {
+            return None;
+        }
+
+        Some((pixel_x as usize * recorded + self.width as usize / 2) / self.width as usize)
+    }
+
+    // Cost of moving onto the hovered cell at the current tick next to the mouse cursor
+    fn draw_tooltip(&self, frame: &mut [u8]) {
+        let Some((pixel_x, pixel_y)) = self.hovered else {
+            return;
+        };
+        let Some((point_coord, _)) = self.grid().cell_at(pixel_x, pixel_y) else {
+            return;
+        };
+        let graph = self.algo.graph();
+        let Some(cost) = graph
+            .schedule()
+            .cost_at(&point_coord, self.tick)
+            .or_else(|| graph.point_at(&point_coord).move_length())
+        else {
+            return;
+        };
+
+        let text = cost.to_string();
+        let (text_width, text_height) = font::text_size(Self::TOOLTIP_SCALE, &text);
+        let (width, height) = (
+            text_width + Self::TOOLTIP_PADDING * 2,
+            text_height + Self::TOOLTIP_PADDING * 2,
+        );
+
+        // Flipped to the other side of the cursor by the borders of the grids
+        let place = |cursor: u32, size: u32, limit: u32| {
+            if cursor + Self::TOOLTIP_OFFSET + size <= limit {
+                cursor + Self::TOOLTIP_OFFSET
+            } else {
+                cursor.saturating_sub(Self::TOOLTIP_OFFSET + size)
+            }
+        };
+        let (x, y) = (
+            place(pixel_x, width, self.grid_viewport().width),
+            place(pixel_y, height, self.height),
+        );
+
+        shapes::fill_rect(
+            frame,
+            self.width,
+            (x as i32, y as i32),
+            (width as i32, height as i32),
+            Self::GRID_COLOR,
+        );
+        font::draw_text(
+            frame,
+            self.width,
+            (x + Self::TOOLTIP_PADDING, y + Self::TOOLTIP_PADDING),
+            Self::TOOLTIP_SCALE,
+            &text,
+            Self::WAYPOINT_COLOR,
+        );
+    }
+
+    fn generate_graph(&mut self, generation: Generation) {
+        self.map = generation.to_string();
+        // Another map is shown whole
+        self.camera.reset();
+
+        self.set_graph(graph::Graph::generate_graph(generation));
+    }
+
+    // Shows a saved graph with its own topology, wrapping and stairs length
+    fn load_graph(&mut self, graph: graph::Graph) {
+        self.map = Editor::SAVE_PATH.to_string();
+        self.camera.reset();
+        self.topology = graph.topology();
+        self.wrap = graph.wrap();
+        self.stairs_length = graph.stairs_length();
+
+        self.set_graph(graph);
+    }
+
+    // Sets another graph, edits of the previous one can't be undone
+    fn set_graph(&mut self, graph: graph::Graph) {
+        self.layer = self.layer.min(graph.layers() - 1);
+        self.inspected = None;
+        self.editor.clear();
+
+        self.apply_graph(graph);
+    }
+
+    // Searches the graph from the beginning keeping the chosen topology, wrapping and stairs length
+    fn apply_graph(&mut self, mut graph: graph::Graph) {
+        graph.set_topology(self.topology);
+        graph.set_wrap(self.wrap);
+        graph.set_stairs_length(self.stairs_length);
+
+        self.max_terrain_cost = graph.max_terrain_cost();
+
+        self.network = None;
+
+        let graph = Arc::new(graph);
+        for compared in &mut self.compared {
+            compared.algo.reset_with(Arc::clone(&graph));
+        }
+        self.algo.reset_with(graph);
+        self.tick = 0;
+        self.alternative = 0;
+    }
+
+    // Edits a copy of the shown graph, the search starts over if it is changed
+    fn edit(&mut self, edit: impl FnOnce(&mut Editor, &mut graph::Graph) -> bool) {
+        let mut graph = self.algo.graph().clone();
+
+        if edit(&mut self.editor, &mut graph) {
+            self.apply_graph(graph);
+        }
+    }
+
+    // Grid-only algorithms bring the grid back
+    fn set_algo(&mut self, new_algo: fn(Arc<graph::Graph>) -> Box<dyn PathFinder>) {
+        let graph = self.algo.graph_wrapper().shared_graph();
+
+        self.network = None;
+
+        self.algo = new_algo(graph);
+        self.new_algo = new_algo;
+        self.observe();
+        // Compared algorithms start over to stay in lockstep with the new one
+        for (algo, _) in self.lockstep() {
+            algo.reset();
+        }
+        self.tick = 0;
+        self.alternative = 0;
+    }
+
+    // The shown algorithm with the compared ones and their metrics
+    fn lockstep(&mut self) -> Vec<(&mut (dyn PathFinder + 'static), &RefCell<SearchMetrics>)> {
+        let mut algos = vec![(self.algo.as_mut(), &*self.metrics)];
+        algos.extend(
+            self.compared
+                .iter_mut()
+                .map(|compared| (compared.algo.as_mut(), &*compared.metrics)),
+        );
+
+        algos
+    }
+
+    // Compares a new copy of the shown algorithm with the ones shown next,
+    // all of them start over to be stepped in lockstep
+    fn compare(&mut self) {
+        if self.compared.len() == Self::MAX_COMPARED {
+            println!("[I] Compared: at most {}", Self::MAX_COMPARED + 1);
+            return;
+        }
+
+        let mut algo = (self.new_algo)(self.algo.graph_wrapper().shared_graph());
+        if let Some(bound) = self.algo.bound() {
+            algo.set_bound(bound);
+        }
+        let metrics = Rc::new(RefCell::new(SearchMetrics::default()));
+        algo.subscribe(Box::new(Rc::clone(&metrics)));
+        self.compared.push(Compared { algo, metrics });
+
+        for (algo, _) in self.lockstep() {
+            algo.reset();
+        }
+        self.tick = 0;
+        self.alternative = 0;
+    }
+
+    // Bounded algorithms get the new bound, all of them start over to stay in lockstep
+    fn set_bound(&mut self, bound: usize) {
+        for (algo, _) in self.lockstep() {
+            algo.set_bound(bound);
+            algo.reset();
+        }
+        self.tick = 0;
+        self.alternative = 0;
+
+        println!("[I] Bound: {}", bound);
+    }
+
+    // Algorithms running on any `SearchGraph`, set for the shown graph
+    fn set_generic_algo(
+        &mut self,
+        new_algo: fn(Arc<graph::Graph>) -> Box<dyn PathFinder>,
+        new_network_algo: fn(Arc<Network>) -> Box<dyn PathFinder<Network>>,
+    ) {
+        match &mut self.network {
+            Some(network) => {
+                let graph = network.graph_wrapper().shared_graph();
+
+                *network = new_network_algo(graph);
+                self.observe();
+            }
+            None => self.set_algo(new_algo),
+        }
+    }
+
+    // Moves the time axis on while playing and makes the due steps while auto-playing
+    fn update(&mut self) {
+        if self.playing && Instant::now() >= self.next_tick {
+            self.tick += 1;
+            self.next_tick = Instant::now() + Self::TICK_DURATION;
+        }
+
+        if self.auto_play && Instant::now() >= self.next_step {
+            let steps = self.steps_per_second.div_ceil(Self::FRAMES_PER_SECOND);
+
+            let completed = match &mut self.network {
+                Some(network) => auto_step(network.as_mut(), &self.metrics, steps),
+                None => {
+                    let mut completed = true;
+                    for (algo, metrics) in self.lockstep() {
+                        completed &= auto_step(algo, metrics, steps);
+                    }
+                    completed
+                }
+            };
+            if completed {
+                self.auto_play = false;
+                println!("[I] Auto-play: off");
+            }
+
+            let step_duration = Duration::from_secs(1) / self.steps_per_second;
+            let frame_duration = Duration::from_secs(1) / Self::FRAMES_PER_SECOND;
+            self.next_step = Instant::now() + step_duration.max(frame_duration);
+        }
+    }
+
+    // The event loop waits for the input until the next tick or step is due
+    fn control_flow(&self) -> ControlFlow {
+        let wake_ups = [
+            self.playing.then_some(self.next_tick),
+            self.auto_play.then_some(self.next_step),
+        ];
+
+        match wake_ups.into_iter().flatten().min() {
+            Some(wake_up) => ControlFlow::WaitUntil(wake_up),
+            None => ControlFlow::Wait,
+        }
+    }
+}
+
+impl<'a> Grid<'a> {
+    fn new(world: &'a World, algo: &'a dyn PathFinder, viewport: Viewport) -> Self {
+        let layers = if world.side_by_side {
+            (0..algo.graph().layers()).collect()
+        } else {
+            vec![world.layer]
+        };
+
+        Self {
+            world,
+            algo,
+            viewport,
+            layers,
+        }
+    }
+
+    // The agent is where it has arrived by the current tick
+    fn markers(&self, alternative: Option<&GraphPath>) -> Markers {
+        let alternative = alternative.map_or_else(HashSet::new, |alternative| {
+            let points = alternative.points();
+
+            HashSet::from_iter(points[1..points.len() - 1].iter().cloned())
+        });
+        let agent = self
+            .algo
+            .timed_path()
+            .filter(|_| !self.is_rewound())
+            .and_then(|timed_path| {
+                let arrived =
+                    timed_path.partition_point(|timed_point| timed_point.t <= self.world.tick);
+
+                timed_path[..arrived].last()
+            })
+            .map(|timed_point| timed_point.coord.clone());
+        let doors = self
+            .algo
+            .graph()
+            .schedule()
+            .doors()
+            .iter()
+            .map(|door| (door.coord.clone(), door.is_open_at(self.world.tick)))
+            .collect();
+
+        Markers {
+            alternative,
+            agent,
+            doors,
+        }
+    }
+
+    // Planned agent, reserved (other agents') cells and doors at the current tick,
+    // or the shown alternative path
+    fn marker_color(&self, point_coord: &PointCoord, markers: &Markers) -> Option<&[u8]> {
+        if markers.alternative.contains(point_coord) {
+            return Some(
+                World::ALTERNATIVE_COLOR[self.world.alternative % World::ALTERNATIVE_COLOR.len()],
+            );
+        }
+
+        if markers.agent.as_ref() == Some(point_coord) {
+            return Some(World::AGENT_COLOR);
+        }
+
+        if let Some(reservation_table) = self.algo.reservation_table() {
+            if reservation_table.is_reserved(point_coord, self.world.tick) {
+                return Some(World::RESERVED_COLOR);
+            }
+        }
+
+        if let Some(&is_open) = markers.doors.get(point_coord) {
+            return Some(if is_open {
+                World::DOOR_OPEN_COLOR
+            } else {
+                World::DOOR_CLOSED_COLOR
+            });
+        }
+
+        None
+    }
+
+    // Width of the viewport part a shown layer is drawn in
+    fn view_width(&self) -> u32 {
+        self.viewport.width / self.layers.len() as u32
+    }
+
+    // Left border of the viewport part the layer is drawn in, `None` if it isn't shown
+    fn view_x(&self, layer: usize) -> Option<u32> {
+        let view = self.layers.iter().position(|&shown| shown == layer)?;
+
+        Some(self.viewport.x + view as u32 * self.view_width())
+    }
+
+    // Side of the square cells fitting the whole layer into its view when not zoomed.
+    // Cells of large graphs are at least a pixel, the layer doesn't fit into its view then.
+    fn cell_side(&self) -> u32 {
+        let (n, m) = (self.algo.graph().n() as f32, self.algo.graph().m() as f32);
+        let (width, height) = self.view_size();
+
+        ((width / m).min(height / n) * self.world.camera.zoom).max(1.0) as u32
+    }
+
+    // Hex size (center to corner) fitting the whole layer into its part of the window when not zoomed
+    fn hex_size(&self, orientation: HexOrientation) -> f32 {
+        let (n, m) = (self.algo.graph().n() as f32, self.algo.graph().m() as f32);
+        let (width, height) = (self.view_width() as f32, self.viewport.height as f32);
+
+        let size = match orientation {
+            HexOrientation::PointyTop => {
+                (width / (SQRT_3 * (m + 0.5))).min(height / (1.5 * n + 0.5))
+            }
+            HexOrientation::FlatTop => (width / (1.5 * m + 0.5)).min(height / (SQRT_3 * (n + 0.5))),
+        };
+
+        size * self.world.camera.zoom
+    }
+
+    // Distance from the center of a cell to its edge
+    fn cell_radius(&self) -> u32 {
+        match self.algo.graph().topology() {
+            Topology::Square => self.cell_side() / 2,
+            Topology::Hex(orientation) => (SQRT_3 / 2.0 * self.hex_size(orientation)) as u32,
+        }
+    }
+
+    // Border of marked cells shrinks with the cells, tiny ones are filled with the mark
+    fn cell_border(&self) -> u32 {
+        (self.cell_radius() / 3).min(World::SEEN_CELL_BORDER)
+    }
+
+    // Size of a shown layer, larger than its view when zoomed in
+    fn layer_size(&self) -> (f32, f32) {
+        let (n, m) = (self.algo.graph().n() as f32, self.algo.graph().m() as f32);
+
+        match self.algo.graph().topology() {
+            Topology::Square => {
+                let side = self.cell_side() as f32;
+
+                (side * m, side * n)
+            }
+            Topology::Hex(orientation) => {
+                let size = self.hex_size(orientation);
+
+                match orientation {
+                    HexOrientation::PointyTop => {
+                        (SQRT_3 * size * (m + 0.5), size * (1.5 * n + 0.5))
+                    }
+                    HexOrientation::FlatTop => (size * (1.5 * m + 0.5), SQRT_3 * size * (n + 0.5)),
+                }
+            }
+        }
+    }
+
+    fn view_size(&self) -> (f32, f32) {
+        (self.view_width() as f32, self.viewport.height as f32)
+    }
+
+    // Pixels of the shown layers left of and above their views,
+    // negative for the layers letterboxed in them
+    fn camera_offset(&self) -> (i32, i32) {
+        let (x, y) = self
+            .world
+            .camera
+            .offset(self.layer_size(), self.view_size());
+
+        (x as i32, y as i32)
+    }
+
+    // Rows and columns of the cells in the views of the shown layers, by one more around
+    // for the shoved rows (columns) of hexes
+    fn visible_cells(&self) -> (Range<usize>, Range<usize>) {
+        let graph = self.algo.graph();
+        let (row_height, column_width) = match graph.topology() {
+            Topology::Square => (self.cell_side() as f32, self.cell_side() as f32),
+            Topology::Hex(orientation) => {
+                let size = self.hex_size(orientation);
+
+                match orientation {
+                    HexOrientation::PointyTop => (1.5 * size, SQRT_3 * size),
+                    HexOrientation::FlatTop => (SQRT_3 * size, 1.5 * size),
+                }
+            }
+        };
+        let (offset_x, offset_y) = self
+            .world
+            .camera
+            .offset(self.layer_size(), self.view_size());
+        let (view_width, view_height) = self.view_size();
+
+        let cells = |offset: f32, view: f32, cell: f32, count: usize| {
+            let first = (offset / cell).floor() as i64 - 1;
+            let last = ((offset + view) / cell).ceil() as i64 + 1;
+
+            first.clamp(0, count as i64) as usize..last.clamp(0, count as i64) as usize
+        };
+
+        (
+            cells(offset_y, view_height, row_height, graph.n()),
+            cells(offset_x, view_width, column_width, graph.m()),
+        )
+    }
+
+    // Point under the pixel and the pixel's distance to the point's edge
+    fn cell_at(&self, pixel_x: u32, pixel_y: u32) -> Option<(PointCoord, u32)> {
+        let (n, m) = (self.algo.graph().n(), self.algo.graph().m());
+
+        // Relative to the viewport
+        let pixel_x = pixel_x
+            .checked_sub(self.viewport.x)
+            .filter(|&x| x < self.viewport.width)?;
+        let pixel_y = pixel_y
+            .checked_sub(self.viewport.y)
+            .filter(|&y| y < self.viewport.height)?;
+
+        let view_width = self.view_width();
+        let layer = *self.layers.get((pixel_x / view_width) as usize)?;
+
+        // Relative to the layer
+        let (offset_x, offset_y) = self.camera_offset();
+        let pixel_x = u32::try_from((pixel_x % view_width) as i32 + offset_x).ok()?;
+        let pixel_y = u32::try_from(pixel_y as i32 + offset_y).ok()?;
+
+        match self.algo.graph().topology() {
+            Topology::Square => {
+                let side = self.cell_side();
+
+                let point_i = (pixel_y / side) as usize;
+                let point_j = (pixel_x / side) as usize;
+
+                if point_i >= n || point_j >= m {
+                    return None;
+                }
+
+                let in_cell_y = pixel_y % side;
+                let in_cell_x = pixel_x % side;
+
+                let distance = in_cell_y
+                    .min(side - in_cell_y)
+                    .min(in_cell_x)
+                    .min(side - in_cell_x);
+
+                Some((
+                    PointCoord {
+                        y: point_i,
+                        x: point_j,
+                        layer,
+                    },
+                    distance,
+                ))
+            }
+            Topology::Hex(orientation) => {
+                let size = self.hex_size(orientation);
+                let (x, y) = (pixel_x as f32, pixel_y as f32);
+
+                // Fractional axial coordinates relative to the center of (0, 0)
+                let (q, r) = match orientation {
+                    HexOrientation::PointyTop => {
+                        let (x, y) = (x - SQRT_3 * size / 2.0, y - size);
+                        ((SQRT_3 / 3.0 * x - y / 3.0) / size, (2.0 / 3.0 * y) / size)
+                    }
+                    HexOrientation::FlatTop => {
+                        let (x, y) = (x - size, y - SQRT_3 * size / 2.0);
+                        ((2.0 / 3.0 * x) / size, (SQRT_3 / 3.0 * y - x / 3.0) / size)
+                    }
+                };
+                let s = -q - r;
+
+                // Cube rounding, the coordinate with the largest error is restored from the others
+                let (mut q_round, mut r_round, s_round) = (q.round(), r.round(), s.round());
+                let (q_diff, r_diff, s_diff) = (
+                    (q_round - q).abs(),
+                    (r_round - r).abs(),
+                    (s_round - s).abs(),
+                );
+                if q_diff > r_diff && q_diff > s_diff {
+                    q_round = -r_round - s_round;
+                } else if r_diff > s_diff {
+                    r_round = -q_round - s_round;
+                }
+
+                let (dq, dr) = (q - q_round, r - r_round);
+                let ds = -dq - dr;
+                let apothem = SQRT_3 / 2.0 * size;
+                let distance =
+                    (1.0 - (dq - dr).abs().max((dr - ds).abs()).max((ds - dq).abs())) * apothem;
+
+                let (point_i, point_j) = orientation.to_offset(AxialCoord {
+                    q: q_round as i32,
+                    r: r_round as i32,
+                });
+
+                if point_i < 0 || point_j < 0 || point_i as usize >= n || point_j as usize >= m {
+                    return None;
+                }
+
+                Some((
+                    PointCoord {
+                        y: point_i as usize,
+                        x: point_j as usize,
+                        layer,
+                    },
+                    distance.max(0.0) as u32,
+                ))
+            }
+        }
+    }
+
+    // Pixel at the center of the point, `None` if its layer isn't shown
+    // or the point is out of the view
+    fn cell_center(&self, point_coord: &PointCoord) -> Option<(u32, u32)> {
+        let (x, y) = self.center_at(
+            point_coord.layer,
+            (point_coord.y as i32, point_coord.x as i32),
+        )?;
+
+        let view_x = self.view_x(point_coord.layer)? as f32;
+        let (view_width, view_height) = self.view_size();
+        let viewport_y = self.viewport.y as f32;
+        if x < view_x || x >= view_x + view_width || y < viewport_y || y >= viewport_y + view_height
+        {
+            return None;
+        }
+
+        Some((x as u32, y as u32))
+    }
+
+    // Center of the cell at `(i, j)` of the layer, which may lie outside of the graph
+    fn center_at(&self, layer: usize, (i, j): (i32, i32)) -> Option<(f32, f32)> {
+        let view_x = self.view_x(layer)? as f32;
+
+        let (x, y) = match self.algo.graph().topology() {
+            Topology::Square => (
+                self.cell_side() as f32 * (j as f32 + 0.5),
+                self.cell_side() as f32 * (i as f32 + 0.5),
+            ),
+            Topology::Hex(orientation) => {
+                let size = self.hex_size(orientation);
+
+                match orientation {
+                    HexOrientation::PointyTop => (
+                        SQRT_3 * size * (j as f32 + 0.5 * (i & 1) as f32 + 0.5),
+                        1.5 * size * i as f32 + size,
+                    ),
+                    HexOrientation::FlatTop => (
+                        1.5 * size * j as f32 + size,
+                        SQRT_3 * size * (i as f32 + 0.5 * (j & 1) as f32 + 0.5),
+                    ),
+                }
+            }
+        };
+
+        let (offset_x, offset_y) = self.camera_offset();
+        Some((
+            view_x + x - offset_x as f32,
+            self.viewport.y as f32 + y - offset_y as f32,
+        ))
+    }
+
+    // Whether the pixel lies by the edge shared with a neighbor behind a thin wall.
+    // Cells are as close to their pixels as possible, so the neighbor is the nearest one.
+    fn is_wall_pixel(&self, pixel_x: u32, pixel_y: u32, point_coord: &PointCoord) -> bool {
+        let (x, y) = (pixel_x as f32, pixel_y as f32);
+
+        let nearest = self
+            .algo
+            .graph()
+            .topology()
+            .neighbor_coords(point_coord)
+            .into_iter()
+            .flatten()
+            .filter_map(|neighbor| {
+                let (center_x, center_y) = self.center_at(point_coord.layer, neighbor)?;
+                Some(((center_x - x).hypot(center_y - y), neighbor))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let graph = self.algo.graph();
+        nearest
+            .and_then(|(_, neighbor)| graph.coord_at(point_coord.layer, neighbor))
+            .is_some_and(|neighbor| graph.has_wall(point_coord, &neighbor))
+    }
+
+    // Offset coordinates of `to` as a neighbor of `from`,
+    // they lie outside of the graph if the cells are adjacent across its wrapped edge
+    fn neighbor_offset(&self, from: &PointCoord, to: &PointCoord) -> Option<(i32, i32)> {
+        let graph = self.algo.graph();
+
+        graph
+            .topology()
+            .neighbor_coords(from)
+            .into_iter()
+            .flatten()
+            .find(|&neighbor| graph.coord_at(from.layer, neighbor).as_ref() == Some(to))
+    }
+
+    // A step across the wrapped edge leaves one cell up to the edge
+    // and enters the other one from the opposite edge, cells out of the view have no steps
+    fn draw_wrapped_steps(&self, frame: &mut [u8], points: &[PointCoord], color: &[u8]) {
+        for step in points.windows(2) {
+            for (from, to) in [(&step[0], &step[1]), (&step[1], &step[0])] {
+                let Some(to_offset) = self.neighbor_offset(from, to) else {
+                    continue;
+                };
+                if to_offset == (to.y as i32, to.x as i32) || self.cell_center(from).is_none() {
+                    continue;
+                }
+
+                let from_offset = (from.y as i32, from.x as i32);
+                let (Some((from_x, from_y)), Some((to_x, to_y))) = (
+                    self.center_at(from.layer, from_offset),
+                    self.center_at(from.layer, to_offset),
+                ) else {
+                    continue;
+                };
+
+                shapes::draw_thick_line(
+                    frame,
+                    self.world.width,
+                    (from_x as i32, from_y as i32),
+                    (
+                        ((from_x + to_x) / 2.0) as i32,
+                        ((from_y + to_y) / 2.0) as i32,
+                    ),
+                    World::WRAPPED_STEP_RADIUS,
+                    color,
+                );
+            }
+        }
+    }
+
+    fn cell_color(
+        &self,
+        pixel_x: u32,
+        pixel_y: u32,
+        markers: &Markers,
+        frontier_order: &HashMap<PointCoord, usize>,
+    ) -> &[u8] {
+        let Some((point_coord, distance)) = self.cell_at(pixel_x, pixel_y) else {
+            return World::BACKGROUND_COLOR;
+        };
+        let graph = self.algo.graph();
+
+        // Grid lines would cover the whole of tiny cells
+        if distance == 0 && self.cell_radius() >= World::GRID_MIN_CELL_RADIUS {
+            return World::GRID_COLOR;
+        }
+
+        let border = self.cell_border();
+        if distance < World::WALL_WIDTH.min(border)
+            && self.is_wall_pixel(pixel_x, pixel_y, &point_coord)
+        {
+            return World::WALL_COLOR;
+        }
+
+        if let Some(marker_color) = self.marker_color(&point_coord, markers) {
+            if distance >= border * 2 {
+                return marker_color;
+            }
+        }
+
+        // Marked and frontier cells keep a border of their own color
+        match self.mark_color(&point_coord, frontier_order) {
+            Some(mark_color) if distance >= border => mark_color,
+            _ => self.world.color_by_point(graph.point_at(&point_coord)),
+        }
+    }
+
+    // Color of the cell's mark, the frontier ones are colored by their expanding order
+    fn mark_color(
+        &self,
+        point_coord: &PointCoord,
+        frontier_order: &HashMap<PointCoord, usize>,
+    ) -> Option<&'static [u8]> {
+        let mark = self.algo.graph_wrapper().mark(point_coord);
+
+        match frontier_order.get(point_coord) {
+            Some(_) if mark == Mark::Path => World::color_by_mark(mark),
+            Some(&order) => Some(World::frontier_color(order)),
+            None => World::color_by_mark(mark),
+        }
+    }
+
+    // Results of the whole search (alternatives, timed path, waypoints order)
+    // and its frontier are hidden while it's stepped back
+    fn is_rewound(&self) -> bool {
+        self.algo.graph_wrapper().is_rewound()
+    }
+
+    // Search state of `algo` over the graph in the viewport
+    fn draw_grid(&self, frame: &mut [u8]) {
+        let alternative_paths = if self.is_rewound() {
+            vec![]
+        } else {
+            self.algo.alternative_paths()
+        };
+        let alternative = alternative_paths.get(self.world.alternative);
+        let frontier = shown_frontier(self.algo);
+        let frontier_order = frontier_order(&frontier);
+        let markers = self.markers(alternative);
+
+        let viewport = self.viewport;
+        for pixel_y in viewport.y..viewport.y + viewport.height {
+            for pixel_x in viewport.x..viewport.x + viewport.width {
+                let i = ((pixel_y * self.world.width + pixel_x) * 4) as usize;
+
+                let rgba = self.cell_color(pixel_x, pixel_y, &markers, &frontier_order);
+
+                frame[i..i + 4].copy_from_slice(rgba);
+            }
+        }
+
+        self.draw_wrapped_steps(frame, self.algo.graph_wrapper().path(), World::PATH_COLOR);
+        if let Some(alternative) = alternative {
+            let color =
+                World::ALTERNATIVE_COLOR[self.world.alternative % World::ALTERNATIVE_COLOR.len()];
+            self.draw_wrapped_steps(frame, alternative.points(), color);
+        }
+
+        // One-way cells point to their exits, ones not adjacent to it or out of the view
+        // have no arrows
+        let graph = self.algo.graph();
+        for (point_coord, exit) in graph.one_way() {
+            let Some(exit_offset) = self.neighbor_offset(point_coord, exit) else {
+                continue;
+            };
+            if self.cell_center(point_coord).is_none() {
+                continue;
+            }
+
+            let offset = (point_coord.y as i32, point_coord.x as i32);
+            let (Some((center_x, center_y)), Some((exit_x, exit_y))) = (
+                self.center_at(point_coord.layer, offset),
+                self.center_at(point_coord.layer, exit_offset),
+            ) else {
+                continue;
+            };
+            let (dx, dy) = (
+                (exit_x - center_x) * World::ARROW_LENGTH,
+                (exit_y - center_y) * World::ARROW_LENGTH,
+            );
+
+            shapes::draw_arrow(
+                frame,
+                self.world.width,
+                ((center_x - dx) as i32, (center_y - dy) as i32),
+                ((center_x + dx) as i32, (center_y + dy) as i32),
+                World::ARROW_RADIUS,
+                World::ARROW_COLOR,
+            );
+        }
+
+        // Moving obstacles at the current tick
+        for moving_obstacle in graph.schedule().moving_obstacles() {
+            let Some((center_x, center_y)) =
+                self.cell_center(moving_obstacle.coord_at(self.world.tick))
+            else {
+                continue;
+            };
+
+            shapes::draw_disc(
+                frame,
+                self.world.width,
+                (center_x as i32, center_y as i32),
+                World::MOVING_OBSTACLE_RADIUS,
+                World::MOVING_OBSTACLE_COLOR,
+            );
+        }
+
+        // Stairs point up and down to the layers they lead to
+        for stairs in graph.stairs() {
+            let Some((center_x, center_y)) = self.cell_center(stairs) else {
+                continue;
+            };
+            let (center_x, center_y) = (center_x as i32, center_y as i32);
+
+            for exit in graph.stairs_exits(stairs) {
+                let (offset, direction) = if exit.layer > stairs.layer {
+                    (-World::STAIRS_ARROW_OFFSET, -1)
+                } else {
+                    (World::STAIRS_ARROW_OFFSET, 1)
+                };
+                let half = direction * World::STAIRS_ARROW_LENGTH / 2;
+
+                shapes::draw_arrow(
+                    frame,
+                    self.world.width,
+                    (center_x + offset, center_y - half),
+                    (center_x + offset, center_y + half),
+                    World::ARROW_RADIUS,
+                    World::BACKGROUND_COLOR,
+                );
+            }
+        }
 
-use crate::algo::PathFinder;
-use crate::graph::{Generation, Point, PointCoord};
-use crate::{algo, graph};
+        // Paired portals are connected, ones on hidden layers are not
+        for portal in graph.portals() {
+            let (Some((a_x, a_y)), Some((b_x, b_y))) =
+                (self.cell_center(&portal.a), self.cell_center(&portal.b))
+            else {
+                continue;
+            };
 
-const WIDTH: u32 = 800;
-const HEIGHT: u32 = 600;
+            shapes::draw_line(
+                frame,
+                self.world.width,
+                (a_x as i32, a_y as i32),
+                (b_x as i32, b_y as i32),
+                World::PORTAL_COLOR,
+            );
+        }
 
-struct World {
-    row_height: u32,
-    column_width: u32,
+        // Visiting order of waypoints
+        let waypoints_order = if self.is_rewound() {
+            &[]
+        } else {
+            self.algo.waypoints_order()
+        };
+        for (i, waypoint) in waypoints_order.iter().enumerate() {
+            let text = (i + 1).to_string();
+            let Some((center_x, center_y)) = self.cell_center(waypoint) else {
+                continue;
+            };
+            let (text_width, text_height) = font::text_size(World::WAYPOINT_ORDER_SCALE, &text);
 
-    algo: Box<dyn PathFinder>,
-}
+            font::draw_text(
+                frame,
+                self.world.width,
+                (
+                    center_x.saturating_sub(text_width / 2),
+                    center_y.saturating_sub(text_height / 2),
+                ),
+                World::WAYPOINT_ORDER_SCALE,
+                &text,
+                World::BACKGROUND_COLOR,
+            );
+        }
 
-impl World {
-    const SEEN_CELL_BORDER: u32 = 10;
+        if self.world.show_costs {
+            self.draw_costs(frame, &frontier, &frontier_order);
+        }
 
-    const GRID_COLOR: &[u8] = &[0x5e, 0x48, 0xe8, 0xff];
-    const BACKGROUND_COLOR: &[u8] = &[0x18, 0x18, 0x18, 0xff];
-    const START_COLOR: &[u8] = &[0xff, 0x00, 0x00, 0xff];
-    const END_COLOR: &[u8] = &[0xd7, 0x42, 0xf5, 0xff];
-    const PATH_COLOR: &[u8] = &[0xff, 0xb0, 0x00, 0xff];
-    const OBSTACLE_COLOR: [&[u8]; 4] = [
-        &[0x60, 0xbf, 0x74, 0xff],
-        &[0x40, 0xbf, 0x74, 0xbf],
-        &[0x20, 0xbf, 0x74, 0x80],
-        &[0x00, 0xbf, 0x74, 0x40],
-    ];
-    const SEEN_COLOR: &[u8] = &[0xff, 0xff, 0x91, 0xff];
-}
+        self.draw_minimap(frame, &frontier_order);
+    }
 
-impl World {
-    fn new() -> anyhow::Result<Self> {
-        let graph = graph::Graph::generate_graph(Generation::Predefined1);
+    // Whole layer in the bottom right corner of the viewport with its shown part outlined,
+    // only when it doesn't fit into its view. A pixel is colored as the inner part
+    // of the cell it falls into, starts and ends keep their colors.
+    fn draw_minimap(&self, frame: &mut [u8], frontier_order: &HashMap<PointCoord, usize>) {
+        let (layer_width, layer_height) = self.layer_size();
+        let (view_width, view_height) = self.view_size();
+        if layer_width <= view_width && layer_height <= view_height {
+            return;
+        }
 
-        let row_height = HEIGHT / graph.n() as u32;
-        let column_width = WIDTH / graph.m() as u32;
+        let viewport = self.viewport;
+        let size = World::MINIMAP_SIZE.min(viewport.width.min(viewport.height) / 3);
+        let scale = size as f32 / layer_width.max(layer_height);
+        let (width, height) = (
+            ((layer_width * scale) as u32).max(1),
+            ((layer_height * scale) as u32).max(1),
+        );
+        let (left, top) = (
+            viewport.x + viewport.width - width - World::MINIMAP_MARGIN,
+            viewport.y + viewport.height - height - World::MINIMAP_MARGIN,
+        );
 
-        let algo = algo::Dijkstra::new(graph);
+        let graph = self.algo.graph();
+        let (n, m) = (graph.n() as u32, graph.m() as u32);
+        let layer = self.layers[0];
+        let markers = self.markers(None);
+        for y in 0..height {
+            for x in 0..width {
+                let point_coord = PointCoord {
+                    y: (y * n / height) as usize,
+                    x: (x * m / width) as usize,
+                    layer,
+                };
+                let point = graph.point_at(&point_coord);
+                let rgba = match point {
+                    Point::Start | Point::End => self.world.color_by_point(point),
+                    _ => self
+                        .marker_color(&point_coord, &markers)
+                        .or_else(|| self.mark_color(&point_coord, frontier_order))
+                        .unwrap_or_else(|| self.world.color_by_point(point)),
+                };
 
-        Ok(Self {
-            row_height,
-            column_width,
-            algo,
-        })
+                let i = (((top + y) * self.world.width + left + x) * 4) as usize;
+                frame[i..i + 4].copy_from_slice(rgba);
+            }
+        }
+        shapes::draw_rect(
+            frame,
+            self.world.width,
+            (left as i32 - 1, top as i32 - 1),
+            (width as i32 + 2, height as i32 + 2),
+            World::GRID_COLOR,
+        );
+
+        let (offset_x, offset_y) = self
+            .world
+            .camera
+            .offset((layer_width, layer_height), (view_width, view_height));
+        shapes::draw_rect(
+            frame,
+            self.world.width,
+            (
+                left as i32 + (offset_x * scale) as i32,
+                top as i32 + (offset_y * scale) as i32,
+            ),
+            (
+                ((view_width.min(layer_width) * scale) as i32).max(1),
+                ((view_height.min(layer_height) * scale) as i32).max(1),
+            ),
+            World::WAYPOINT_COLOR,
+        );
     }
 
-    fn color_by_point<'a>(&'a self, point: &'a Point) -> &'a [u8] {
-        return match point {
-            &Point::Start => Self::START_COLOR,
-            &Point::End => Self::END_COLOR,
-            &Point::Free => Self::BACKGROUND_COLOR,
-            &Point::Obstacle { length } => {
-                assert!(length < 4);
-                Self::OBSTACLE_COLOR[length as usize]
+    // Name of the algorithm in the top left corner of its viewport
+    fn draw_name(&self, frame: &mut [u8]) {
+        let name = self.algo.name();
+        let (text_width, text_height) = font::text_size(World::TOOLTIP_SCALE, name);
+
+        shapes::fill_rect(
+            frame,
+            self.world.width,
+            (self.viewport.x as i32, self.viewport.y as i32),
+            (
+                (text_width + World::TOOLTIP_PADDING * 2) as i32,
+                (text_height + World::TOOLTIP_PADDING * 2) as i32,
+            ),
+            World::GRID_COLOR,
+        );
+        font::draw_text(
+            frame,
+            self.world.width,
+            (
+                self.viewport.x + World::TOOLTIP_PADDING,
+                self.viewport.y + World::TOOLTIP_PADDING,
+            ),
+            World::TOOLTIP_SCALE,
+            name,
+            World::WAYPOINT_COLOR,
+        );
+    }
+
+    // `f` of every reached cell with `g` below it on the left and `h` on the right,
+    // only in the cells large enough for them. Frontier cells show the ones they wait with.
+    fn draw_costs(
+        &self,
+        frame: &mut [u8],
+        frontier: &[FrontierNode<PointCoord>],
+        frontier_order: &HashMap<PointCoord, usize>,
+    ) {
+        // No labels fit into small cells
+        if self.cell_radius() < World::SEEN_CELL_BORDER {
+            return;
+        }
+
+        for frontier_node in frontier {
+            self.draw_cost_label(frame, &frontier_node.node, frontier_node.g, frontier_node.h);
+        }
+
+        let graph_wrapper = self.algo.graph_wrapper();
+        let (rows, columns) = self.visible_cells();
+        for &layer in &self.layers {
+            for y in rows.clone() {
+                for x in columns.clone() {
+                    let point_coord = PointCoord { x, y, layer };
+                    if frontier_order.contains_key(&point_coord) {
+                        continue;
+                    }
+                    let Some((_, g)) = graph_wrapper.came_from(&point_coord) else {
+                        continue;
+                    };
+
+                    self.draw_cost_label(frame, &point_coord, g, self.algo.heuristic(&point_coord));
+                }
             }
-            &Point::Path { .. } => Self::PATH_COLOR,
-            &Point::Seen { .. } => Self::SEEN_COLOR,
-        };
+        }
     }
 
-    fn initial_color_by_point<'a>(&'a self, point: &'a Point) -> Option<&'a [u8]> {
-        return match point {
-            &Point::Path { ref initial_point } => Some(self.color_by_point(&initial_point)),
-            &Point::Seen { ref initial_point } => Some(self.color_by_point(&initial_point)),
-            _ => None,
+    fn draw_cost_label(&self, frame: &mut [u8], point_coord: &PointCoord, g: u32, h: u32) {
+        let Some((center_x, center_y)) = self.cell_center(point_coord) else {
+            return;
         };
+
+        let (f, g, h) = ((g + h).to_string(), g.to_string(), h.to_string());
+        let (f_width, f_height) = font::text_size(World::COST_SCALE, &f);
+        let (g_width, g_height) = font::text_size(1, &g);
+        let (h_width, _) = font::text_size(1, &h);
+
+        let width = f_width.max(g_width + h_width + World::COST_SPACING);
+        let height = f_height + World::COST_SPACING + g_height;
+        let (left, top) = (
+            center_x.saturating_sub(width / 2),
+            center_y.saturating_sub(height / 2),
+        );
+
+        let fits = [
+            (left, top),
+            (left + width, top),
+            (left, top + height),
+            (left + width, top + height),
+        ]
+        .into_iter()
+        .all(|(x, y)| {
+            matches!(
+                self.cell_at(x, y),
+                Some((corner_coord, distance))
+                    if &corner_coord == point_coord && distance >= self.cell_border()
+            )
+        });
+        if !fits {
+            return;
+        }
+
+        let bottom = top + f_height + World::COST_SPACING;
+        for (x, y, scale, text) in [
+            (center_x - f_width / 2, top, World::COST_SCALE, &f),
+            (left, bottom, 1, &g),
+            (left + width - h_width, bottom, 1, &h),
+        ] {
+            font::draw_text(
+                frame,
+                self.world.width,
+                (x, y),
+                scale,
+                text,
+                World::BACKGROUND_COLOR,
+            );
+        }
     }
+}
 
-    fn cell_color<'a>(&'a self, pixel_x: u32, pixel_y: u32) -> &'a [u8] {
-        let point_i = pixel_y / self.row_height;
-        let point_j = pixel_x / self.column_width;
+// Frontier of the shown state with the best entry of every node, a space-time search
+// has one for every timestep. Hidden while stepped back.
+fn shown_frontier<G: SearchGraph>(algo: &dyn PathFinder<G>) -> Vec<FrontierNode<G::Node>> {
+    if algo.graph_wrapper().is_rewound() {
+        return vec![];
+    }
 
-        let start_y_1 = self.row_height * point_i as u32;
-        let start_y_2 = self.row_height * (point_i + 1) as u32;
+    let mut nodes = HashSet::new();
+    let mut frontier = algo.frontier();
+    frontier.retain(|frontier_node| nodes.insert(frontier_node.node.clone()));
 
-        let start_x_1 = self.column_width * point_j as u32;
-        let start_x_2 = self.column_width * (point_j + 1) as u32;
+    frontier
+}
 
-        let point = self.algo.point_at(&PointCoord {
-            y: point_i as usize,
-            x: point_j as usize,
-        });
+// Progress of the shown search for the HUD
+fn search_numbers<G: SearchGraph>(algo: &dyn PathFinder<G>) -> [(&'static str, String); 4] {
+    let graph_wrapper = algo.graph_wrapper();
+    let (applied, recorded) = graph_wrapper.timeline();
 
-        if (pixel_y >= start_y_1 && pixel_y <= start_y_2)
-            && (pixel_x >= start_x_1 && pixel_x <= start_x_2)
-        {
-            if let Some(initial_color) = self.initial_color_by_point(point) {
-                if !((pixel_y >= start_y_1 + Self::SEEN_CELL_BORDER
-                    && pixel_y + Self::SEEN_CELL_BORDER <= start_y_2)
-                    && (pixel_x >= start_x_1 + Self::SEEN_CELL_BORDER
-                        && pixel_x + Self::SEEN_CELL_BORDER <= start_x_2))
-                {
-                    return initial_color;
-                }
-            }
+    [
+        ("Step", format!("{applied}/{recorded}")),
+        ("Seen", graph_wrapper.seen_points.to_string()),
+        ("Frontier", shown_frontier(algo).len().to_string()),
+        (
+            "Length",
+            graph_wrapper
+                .path_length()
+                .map_or("-".to_string(), |length| length.to_string()),
+        ),
+    ]
+}
 
-            return self.color_by_point(point);
-        }
+// Position of every frontier node in the expanding order
+fn frontier_order<N: Clone + Eq + std::hash::Hash>(
+    frontier: &[FrontierNode<N>],
+) -> HashMap<N, usize> {
+    frontier
+        .iter()
+        .enumerate()
+        .map(|(order, frontier_node)| (frontier_node.node.clone(), order))
+        .collect()
+}
 
-        Self::BACKGROUND_COLOR
+// Makes (or replays a recorded) step and prints the path with the metrics
+// once the algorithm completes
+fn step<G: SearchGraph>(algo: &mut dyn PathFinder<G>, metrics: &RefCell<SearchMetrics>) {
+    if !algo.is_completed() && !is_stuck(algo) {
+        algo.step_forward();
+        print_step(algo);
+        print_end(algo, metrics);
     }
+}
 
-    fn draw(&self, frame: &mut [u8]) {
-        for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
-            let pixel_x = i as u32 % WIDTH;
-            let pixel_y = i as u32 / WIDTH;
+// Makes up to `steps` steps, `true` once the algorithm completes or can't go on.
+// Steps aren't printed, there are too many of them.
+fn auto_step<G: SearchGraph>(
+    algo: &mut dyn PathFinder<G>,
+    metrics: &RefCell<SearchMetrics>,
+    steps: u32,
+) -> bool {
+    for _ in 0..steps {
+        if algo.is_completed() || is_stuck(algo) {
+            break;
+        }
 
-            let mut rgba = self.cell_color(pixel_x, pixel_y);
+        algo.step_forward();
+        print_end(algo, metrics);
+    }
 
-            if pixel_y % self.row_height == 0 || pixel_x % self.column_width == 0 {
-                rgba = Self::GRID_COLOR
-            }
+    algo.is_completed() || is_stuck(algo)
+}
 
-            pixel.copy_from_slice(&rgba);
+// Makes all the steps at once printing only the last one and the result
+fn run_to_completion<G: SearchGraph>(
+    algo: &mut dyn PathFinder<G>,
+    metrics: &RefCell<SearchMetrics>,
+) {
+    if !algo.is_completed() && !is_stuck(algo) {
+        while !algo.is_completed() && !is_stuck(algo) {
+            algo.step_forward();
         }
+        print_step(algo);
+        print_end(algo, metrics);
+    }
+}
+
+// Result of a completed search or why it stopped, nothing while it goes on
+fn print_end<G: SearchGraph>(algo: &mut dyn PathFinder<G>, metrics: &RefCell<SearchMetrics>) {
+    if algo.is_completed() {
+        print_result(algo, metrics);
+    } else if is_stuck(algo) {
+        print_stuck(algo);
+    }
+}
+
+// The search isn't completed but a new step would make no progress:
+// the last one changed nothing with nothing left in the frontier, or there are too many steps
+fn is_stuck<G: SearchGraph>(algo: &dyn PathFinder<G>) -> bool {
+    let graph_wrapper = algo.graph_wrapper();
+    if algo.is_completed() || graph_wrapper.is_rewound() {
+        return false;
+    }
+
+    graph_wrapper.timeline().1 >= MAX_RECORDED_STEPS
+        || (graph_wrapper.is_last_step_idle() && algo.frontier().is_empty())
+}
+
+fn print_stuck<G: SearchGraph>(algo: &dyn PathFinder<G>) {
+    if algo.graph_wrapper().timeline().1 >= MAX_RECORDED_STEPS {
+        println!("[I] Stopped after {MAX_RECORDED_STEPS} steps. Path is not found");
+    } else {
+        println!("[I] Stopped. The search makes no progress");
+    }
+}
+
+fn print_result<G: SearchGraph>(algo: &mut dyn PathFinder<G>, metrics: &RefCell<SearchMetrics>) {
+    if let Some(path) = algo.build_path() {
+        println!("[I] {path}");
+    } else {
+        println!("[I] Completed. Path is not found");
+    }
+    println!("[I] {}", metrics.borrow());
+}
+
+fn step_back<G: SearchGraph>(algo: &mut dyn PathFinder<G>) {
+    if algo.step_back() {
+        print_step(algo);
+    }
+}
+
+fn jump_to<G: SearchGraph>(algo: &mut dyn PathFinder<G>, step: usize) {
+    algo.graph_wrapper_mut().jump_to(step);
+    print_step(algo);
+}
+
+// Position on the timeline with the expanded and relaxed nodes of the last applied step
+fn print_step<G: SearchGraph>(algo: &dyn PathFinder<G>) {
+    let graph_wrapper = algo.graph_wrapper();
+    let (applied, recorded) = graph_wrapper.timeline();
+
+    match graph_wrapper.last_step() {
+        Some((expanded, relaxed)) => println!(
+            "[I] Step {applied}/{recorded}: expanded {}, relaxed {}",
+            expanded.map_or("none".to_string(), |node| format!("[{node}]")),
+            relaxed.len()
+        ),
+        None => println!("[I] Step {applied}/{recorded}"),
     }
+}
+
+// Size of the pixel buffer filling the window. The buffer is scaled up by whole factors only,
+// so a pixel of it covers as many physical pixels as the rounded HiDPI scale factor.
+fn buffer_size(window: &Window) -> (u32, u32) {
+    let size = window.inner_size();
+    let scale = window.scale_factor().round().max(1.0) as u32;
 
-    fn update(&mut self) {}
+    ((size.width / scale).max(1), (size.height / scale).max(1))
 }
 
 pub fn start_ui() -> anyhow::Result<()> {
@@ -142,13 +2228,14 @@ pub fn start_ui() -> anyhow::Result<()> {
             .with_context(|| "Could not create window")?
     };
 
+    let (width, height) = buffer_size(&window);
     let mut pixels = {
         let window_size = window.inner_size();
         let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(WIDTH, HEIGHT, surface_texture)?
+        Pixels::new(width, height, surface_texture)?
     };
 
-    let mut world = World::new().with_context(|| "Could not create a world")?;
+    let mut world = World::new(width, height).with_context(|| "Could not create a world")?;
 
     event_loop.run(move |event, _, control_flow| {
         if let Event::RedrawRequested(_) = event {
@@ -160,91 +2247,551 @@ pub fn start_ui() -> anyhow::Result<()> {
         }
 
         if input.update(&event) {
-            // Resize
-            if let Some(size) = input.window_resized() {
-                if let Err(_err) = pixels.resize_surface(size.width, size.height) {
+            // Resize, the buffer is resized with the window and the layout follows it
+            if input.window_resized().is_some() || input.scale_factor_changed().is_some() {
+                let size = window.inner_size();
+                let (width, height) = buffer_size(&window);
+
+                if pixels.resize_surface(size.width, size.height).is_err()
+                    || pixels.resize_buffer(width, height).is_err()
+                {
                     *control_flow = ControlFlow::Exit;
                     return;
                 }
+                world.resize(width, height);
             }
 
+            let previous_hovered = world.hovered;
+            world.hovered = input
+                .mouse()
+                .and_then(|position| pixels.window_pos_to_pixel(position).ok())
+                .map(|(x, y)| (x as u32, y as u32));
+
             // Keys
             /*
-                `s` - make algorithm's step
+                `s` - make algorithm's step (replay the next one if stepped back)
+                `shift+s` - run algorithm to completion
+                `space` - auto-play (pause) algorithm's steps
+                `page up` - speed up auto-play (steps per second, several steps a frame when fast)
+                `page down` - slow down auto-play
+                `z` - step back
+                `home` - jump to the beginning of the recorded steps
+                `end` - jump to the last recorded step
                 `r` - reset graph to initial state
+                `c` - switch showing `f` (and `g`, `h` below it) of the reached cells
+                mouse click on a cell - inspect it (predecessor, costs, steps), again - close
+                `enter` - switch map editor (mouse paints the map instead of inspecting cells)
+                    left drag - paint obstacles, drag a start or an end - move it
+                    right drag - erase obstacles and terrain
+                    `j` - switch difficulty of the painted obstacles
+                    `ctrl+z` - undo edit
+                    `ctrl+y` - redo edit
+                    `ctrl+s` - save map to `map.txt`
+                    `ctrl+o` - load map from `map.txt`
+                mouse wheel - zoom in (out) at the cursor
+                middle drag, right drag out of the editor - pan
+                `backspace` - show the whole graph again (reset zoom and pan)
+                `v` - switch printing every event of the search (pushed, popped, relaxed nodes)
 
                 `d` - set dijksta's algorithm
                 `b` - set bfs algorithm
                 `h` - set heuristic algorithm
                 `a` - set a-star algorithm
+                `t` - set space-time a-star algorithm
+                `i` - set time-dependent a-star algorithm
+                `u` - set time-dependent dijkstra's algorithm
+                `y` - set k shortest paths (yen's) algorithm
+                `w` - set waypoints algorithm
+                `e` - set beam search algorithm
+                `m` - set frontier bounded a-star algorithm
+                `\` - compare a copy of the shown algorithm with the ones set next (stepped in lockstep)
+                `delete` - stop comparing
+
+                `x` - switch grid topology (square, pointy top hex, flat top hex)
+                `o` - switch wrapping around the edges (torus)
+                `f` - show next layer (floor) of a multi-level graph
+                `g` - switch showing all layers side by side
+                `k` - decrease length of going one layer up or down the stairs
+                `l` - increase length of going one layer up or down the stairs
+
+                `[` - move time axis one tick back
+                `]` - move time axis one tick forward
+                `p` - play (pause) time axis
+                `tab` - show next alternative path
+                `,` - decrease bound (beam width, max frontier) of a bounded algorithm
+                `.` - increase bound (beam width, max frontier) of a bounded algorithm
 
                 `1` - set 1' predefined graph
                 `2` - set 2' predefined graph
+                `3` - set 3' predefined graph (with waypoints)
+                `4` - set 4' predefined graph (with several starts and ends)
+                `5` - set 5' predefined graph (with one-way cells and thin walls)
+                `6` - set 6' predefined graph (with portals)
+                `7` - set 7' predefined graph (with several layers, stairs and an elevator)
+                `8` - generate random terrain graph (heightmap of movement costs)
+                `9` - set graph with a door, a moving obstacle and rush hours
                 `-` - generate ramdom graph
+                `=` - generate ramdom graph with waypoints
+                `n` - generate random road network (dijkstra, bfs, heuristic, a-star only)
+
+                `f1` - show (hide) help with every key
+                `f2` - show (hide) hud (algorithm, map, progress, legend of the colors)
             */
             if input.key_pressed(VirtualKeyCode::Q) || input.close_requested() {
                 *control_flow = ControlFlow::Exit;
                 return;
             }
 
-            if input.key_pressed_os(VirtualKeyCode::S) {
-                if !world.algo.is_completed() {
-                    world.algo.step();
+            if input.key_pressed_os(VirtualKeyCode::S)
+                && !input.held_control()
+                && !input.held_shift()
+            {
+                match &mut world.network {
+                    Some(network) => step(network.as_mut(), &world.metrics),
+                    None => {
+                        for (algo, metrics) in world.lockstep() {
+                            step(algo, metrics);
+                        }
+                    }
+                }
+            }
+
+            if input.key_pressed(VirtualKeyCode::S) && input.held_shift() {
+                match &mut world.network {
+                    Some(network) => run_to_completion(network.as_mut(), &world.metrics),
+                    None => {
+                        for (algo, metrics) in world.lockstep() {
+                            run_to_completion(algo, metrics);
+                        }
+                    }
+                }
+            }
+
+            if input.key_pressed(VirtualKeyCode::Space) {
+                world.auto_play = !world.auto_play;
+                world.next_step = Instant::now();
+                println!(
+                    "[I] Auto-play: {}",
+                    if world.auto_play { "on" } else { "off" }
+                );
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::PageUp) {
+                world.steps_per_second =
+                    (world.steps_per_second * 2).min(World::MAX_STEPS_PER_SECOND);
+                println!("[I] Speed: {} steps per second", world.steps_per_second);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::PageDown) {
+                world.steps_per_second = (world.steps_per_second / 2).max(1);
+                println!("[I] Speed: {} steps per second", world.steps_per_second);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Z) && !input.held_control() {
+                match &mut world.network {
+                    Some(network) => step_back(network.as_mut()),
+                    None => {
+                        for (algo, _) in world.lockstep() {
+                            step_back(algo);
+                        }
+                    }
+                }
+            }
+
+            if input.key_pressed(VirtualKeyCode::Home) {
+                match &mut world.network {
+                    Some(network) => jump_to(network.as_mut(), 0),
+                    None => {
+                        for (algo, _) in world.lockstep() {
+                            jump_to(algo, 0);
+                        }
+                    }
+                }
+            }
+
+            if input.key_pressed(VirtualKeyCode::End) {
+                match &mut world.network {
+                    Some(network) => jump_to(network.as_mut(), usize::MAX),
+                    None => {
+                        for (algo, _) in world.lockstep() {
+                            jump_to(algo, usize::MAX);
+                        }
+                    }
+                }
+            }
+
+            // Timeline slider
+            let timeline_step = world
+                .hovered
+                .filter(|_| input.mouse_held(0))
+                .and_then(|pixel| world.timeline_step_at(pixel));
+            if let Some(timeline_step) = timeline_step {
+                let applied = match &world.network {
+                    Some(network) => network.graph_wrapper().timeline().0,
+                    None => world.algo.graph_wrapper().timeline().0,
+                };
+
+                if applied != timeline_step {
+                    match &mut world.network {
+                        Some(network) => jump_to(network.as_mut(), timeline_step),
+                        None => {
+                            for (algo, _) in world.lockstep() {
+                                jump_to(algo, timeline_step);
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Inspector, the grid only
+            let clicked = world
+                .hovered
+                .filter(|_| input.mouse_pressed(0) && world.network.is_none())
+                .filter(|_| !world.editor.enabled)
+                .filter(|&pixel| world.timeline_step_at(pixel).is_none())
+                .and_then(|(x, y)| world.grid().cell_at(x, y));
+            if let Some((point_coord, _)) = clicked {
+                world.inspected = if world.inspected.as_ref() == Some(&point_coord) {
+                    None
+                } else {
+                    Some(point_coord)
+                };
+            }
+
+            // Map editor, the grid only
+            let editing = world.editor.enabled && world.network.is_none();
+            if editing && (input.mouse_pressed(0) || input.mouse_pressed(1)) {
+                world.editor.begin_stroke();
+            }
+
+            let edited = world
+                .hovered
+                .filter(|_| editing)
+                .filter(|&pixel| world.timeline_step_at(pixel).is_none())
+                .and_then(|(x, y)| world.grid().cell_at(x, y));
+            if let Some((point_coord, _)) = edited {
+                if input.mouse_pressed(0) {
+                    world.editor.grab(world.algo.graph(), &point_coord);
+                }
 
-                    if world.algo.is_completed() {
-                        if let Some(path) = world.algo.build_path() {
-                            println!("[I] {path}");
+                if input.mouse_held(0) {
+                    world.edit(|editor, graph| {
+                        if editor.is_dragging() {
+                            editor.drag_to(graph, &point_coord)
                         } else {
-                            println!("[I] Completed. Path is not found");
+                            editor.paint(graph, &point_coord)
                         }
+                    });
+                } else if input.mouse_held(1) {
+                    world.edit(|editor, graph| editor.erase(graph, &point_coord));
+                }
+            }
+            if input.mouse_released(0) || input.mouse_released(1) {
+                world.editor.end_stroke();
+            }
+
+            // Camera, the grid only: the wheel zooms at the cursor,
+            // middle drag (right drag out of the editor) pans
+            if world.network.is_none() {
+                let scroll = input.scroll_diff();
+                if let Some(pixel) = world.hovered.filter(|_| scroll != 0.0) {
+                    world.zoom_at(pixel, scroll.signum());
+                }
+
+                let panning = input.mouse_held(2) || input.mouse_held(1) && !world.editor.enabled;
+                let dragged = world.hovered.zip(previous_hovered).filter(|_| panning);
+                if let Some(((x, y), (previous_x, previous_y))) = dragged {
+                    world.pan((x as f32 - previous_x as f32, y as f32 - previous_y as f32));
+                }
+            }
+
+            if input.key_pressed(VirtualKeyCode::Back) {
+                world.camera.reset();
+            }
+
+            if input.key_pressed(VirtualKeyCode::Return) {
+                world.editor.enabled = !world.editor.enabled;
+                world.inspected = None;
+                println!(
+                    "[I] Editor: {}",
+                    if world.editor.enabled { "on" } else { "off" }
+                );
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::J) {
+                world.editor.next_difficulty();
+                println!("[I] Obstacle difficulty: {}", world.editor.difficulty);
+            }
+
+            if input.held_control() && input.key_pressed_os(VirtualKeyCode::Z) {
+                world.edit(|editor, graph| editor.undo(graph));
+            }
+
+            if input.held_control() && input.key_pressed_os(VirtualKeyCode::Y) {
+                world.edit(|editor, graph| editor.redo(graph));
+            }
+
+            if input.held_control() && input.key_pressed(VirtualKeyCode::S) {
+                let map = world.algo.graph().to_map_file();
+
+                match std::fs::write(Editor::SAVE_PATH, map) {
+                    Ok(()) => println!("[I] Map saved to {}", Editor::SAVE_PATH),
+                    Err(err) => println!("[E] Could not save the map: {err}"),
+                }
+            }
+
+            if input.held_control() && input.key_pressed(VirtualKeyCode::O) {
+                let graph = std::fs::read_to_string(Editor::SAVE_PATH)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|map| graph::Graph::from_map_file(&map));
+
+                match graph {
+                    Ok(graph) => {
+                        world.load_graph(graph);
+                        println!("[I] Map loaded from {}", Editor::SAVE_PATH);
                     }
+                    Err(err) => println!("[E] Could not load the map: {err}"),
                 }
             }
 
             if input.key_pressed_os(VirtualKeyCode::R) {
-                world.algo.reset()
+                if let Some(network) = &mut world.network {
+                    network.reset();
+                }
+                for (algo, _) in world.lockstep() {
+                    algo.reset();
+                }
+                world.tick = 0;
+                world.alternative = 0;
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::C) {
+                world.show_costs = !world.show_costs;
+                println!("[I] Costs: {}", if world.show_costs { "on" } else { "off" });
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::V) {
+                let mut event_logger = world.event_logger.borrow_mut();
+                event_logger.enabled = !event_logger.enabled;
+                println!(
+                    "[I] Events logging: {}",
+                    if event_logger.enabled { "on" } else { "off" }
+                );
             }
 
             if input.key_pressed_os(VirtualKeyCode::D) {
-                let graph = world.algo.graph().clone();
-                world.algo = algo::Dijkstra::new(graph);
-                world.algo.reset();
+                world.set_generic_algo(algo::Dijkstra::new, algo::Dijkstra::new);
             }
 
             if input.key_pressed_os(VirtualKeyCode::B) {
-                let graph = world.algo.graph().clone();
-                world.algo = algo::Bfs::new(graph);
-                world.algo.reset();
+                world.set_generic_algo(algo::Bfs::new, algo::Bfs::new);
             }
 
             if input.key_pressed_os(VirtualKeyCode::H) {
-                let graph = world.algo.graph().clone();
-                world.algo = algo::Heuristic::new(graph);
-                world.algo.reset();
+                world.set_generic_algo(algo::Heuristic::new, algo::Heuristic::new);
             }
 
             if input.key_pressed_os(VirtualKeyCode::A) {
+                world.set_generic_algo(algo::AStar::new, algo::AStar::new);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::T) {
+                world.set_algo(algo::SpaceTimeAStar::new);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::I) {
+                world.set_algo(algo::TimeDependentAStar::new);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::U) {
+                world.set_algo(algo::TimeDependentAStar::dijkstra);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Y) && !input.held_control() {
+                world.set_algo(algo::KShortestPaths::new);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::W) {
+                world.set_algo(algo::Waypoints::new);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::E) {
+                world.set_algo(algo::BeamSearch::new);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::M) {
+                world.set_algo(algo::FrontierBoundedAStar::new);
+            }
+
+            if input.key_pressed(VirtualKeyCode::Backslash) && world.network.is_none() {
+                world.compare();
+
+                let names = std::iter::once(&world.algo)
+                    .chain(world.compared.iter().map(|compared| &compared.algo))
+                    .map(|algo| algo.name())
+                    .collect::<Vec<_>>();
+                println!("[I] Compared: {}", names.join(", "));
+            }
+
+            if input.key_pressed(VirtualKeyCode::Delete) {
+                world.compared.clear();
+                println!("[I] Compared: off");
+            }
+
+            if input.key_pressed(VirtualKeyCode::F1) {
+                world.show_help = !world.show_help;
+            }
+
+            if input.key_pressed(VirtualKeyCode::F2) {
+                world.show_hud = !world.show_hud;
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::X) {
+                world.topology = world.topology.next();
+                println!("[I] Topology: {}", world.topology);
+
+                let graph = world.algo.graph().clone();
+                world.set_graph(graph);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::O) && !input.held_control() {
+                world.wrap = !world.wrap;
+                println!("[I] Wrap: {}", if world.wrap { "on" } else { "off" });
+
+                let graph = world.algo.graph().clone();
+                world.set_graph(graph);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::F) {
+                world.layer = (world.layer + 1) % world.algo.graph().layers();
+                println!(
+                    "[I] Layer: {}/{}",
+                    world.layer + 1,
+                    world.algo.graph().layers()
+                );
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::G) {
+                world.side_by_side = !world.side_by_side;
+                println!(
+                    "[I] Layers side by side: {}",
+                    if world.side_by_side { "on" } else { "off" }
+                );
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::K) {
+                world.stairs_length = world.stairs_length.saturating_sub(1).max(1);
+                println!("[I] Stairs length: {}", world.stairs_length);
+
+                let graph = world.algo.graph().clone();
+                world.set_graph(graph);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::L) {
+                world.stairs_length += 1;
+                println!("[I] Stairs length: {}", world.stairs_length);
+
                 let graph = world.algo.graph().clone();
-                world.algo = algo::AStar::new(graph);
-                world.algo.reset();
+                world.set_graph(graph);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Comma) {
+                if let Some(bound) = world.algo.bound() {
+                    world.set_bound(bound.saturating_sub(1).max(1));
+                }
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Period) {
+                if let Some(bound) = world.algo.bound() {
+                    world.set_bound(bound + 1);
+                }
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Tab) {
+                let alternative_paths = world.algo.alternative_paths();
+
+                if let Some(shortest) = alternative_paths.first() {
+                    world.alternative = (world.alternative + 1) % alternative_paths.len();
+
+                    let alternative = &alternative_paths[world.alternative];
+                    println!(
+                        "[I] Alternative {}/{}: Length {} (+{})",
+                        world.alternative + 1,
+                        alternative_paths.len(),
+                        alternative.length(),
+                        alternative.length() - shortest.length()
+                    );
+                }
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::LBracket) {
+                world.tick = world.tick.saturating_sub(1);
+                println!("[I] Tick: {}", world.tick);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::RBracket) {
+                world.tick += 1;
+                println!("[I] Tick: {}", world.tick);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::P) {
+                world.playing = !world.playing;
+                world.next_tick = Instant::now() + World::TICK_DURATION;
+                println!("[I] Playing: {}", if world.playing { "on" } else { "off" });
             }
 
             if input.key_pressed_os(VirtualKeyCode::Key1) {
-                let graph = graph::Graph::generate_graph(Generation::Predefined1);
-                world.algo.reset_with(graph);
+                world.generate_graph(Generation::Predefined1);
             }
 
             if input.key_pressed_os(VirtualKeyCode::Key2) {
-                let graph = graph::Graph::generate_graph(Generation::Predefined2);
-                world.algo.reset_with(graph);
+                world.generate_graph(Generation::Predefined2);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key3) {
+                world.generate_graph(Generation::Predefined3);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key4) {
+                world.generate_graph(Generation::Predefined4);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::N) {
+                let network = Arc::new(Network::generate_random());
+                world.network = Some(algo::Dijkstra::new(network));
+                world.observe();
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key5) {
+                world.generate_graph(Generation::Predefined5);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key6) {
+                world.generate_graph(Generation::Predefined6);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key7) {
+                world.generate_graph(Generation::Predefined7);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key8) {
+                world.generate_graph(Generation::Terrain);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Key9) {
+                world.generate_graph(Generation::Dynamic);
             }
 
             if input.key_pressed_os(VirtualKeyCode::Minus) {
-                let graph = graph::Graph::generate_graph(Generation::Random);
-                world.algo.reset_with(graph);
+                world.generate_graph(Generation::Random);
+            }
+
+            if input.key_pressed_os(VirtualKeyCode::Equals) {
+                world.generate_graph(Generation::RandomWaypoints);
             }
 
             world.update();
+            *control_flow = world.control_flow();
             window.request_redraw();
         }
     })
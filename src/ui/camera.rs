@@ -0,0 +1,70 @@
+// Zoom and pan of the grids, the shown algorithm and the compared ones share it.
+// The pan is kept as the parts of the layer left of and above the view,
+// so it stays in place while zooming and in viewports of another size.
+pub struct Camera {
+    // Times the layer is larger than fitted into its view
+    pub zoom: f32,
+    x: f32,
+    y: f32,
+}
+
+impl Camera {
+    pub const ZOOM_STEP: f32 = 1.25;
+    pub const MAX_ZOOM: f32 = 16.0;
+
+    pub fn new() -> Self {
+        Camera {
+            zoom: 1.0,
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+
+    // The whole layer is fitted into its view
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    // Zooms in (out) by `ZOOM_STEP`, `false` if the zoom is at its bound
+    pub fn zoom_by(&mut self, steps: f32) -> bool {
+        let zoom = (self.zoom * Self::ZOOM_STEP.powf(steps)).clamp(1.0, Self::MAX_ZOOM);
+        if zoom == self.zoom {
+            return false;
+        }
+
+        self.zoom = zoom;
+
+        true
+    }
+
+    // Pixels of the layer left of and above the view, the view never leaves the layer
+    pub fn offset(&self, layer_size: (f32, f32), view_size: (f32, f32)) -> (f32, f32) {
+        (
+            Self::place(self.x, layer_size.0, view_size.0),
+            Self::place(self.y, layer_size.1, view_size.1),
+        )
+    }
+
+    pub fn set_offset(
+        &mut self,
+        (x, y): (f32, f32),
+        layer_size: (f32, f32),
+        view_size: (f32, f32),
+    ) {
+        self.x = Self::clamp(x, layer_size.0, view_size.0) / layer_size.0;
+        self.y = Self::clamp(y, layer_size.1, view_size.1) / layer_size.1;
+    }
+
+    // A layer smaller than the view is centered in it (letterboxed), the offset is negative then
+    fn place(part: f32, layer_size: f32, view_size: f32) -> f32 {
+        if layer_size <= view_size {
+            return (layer_size - view_size) / 2.0;
+        }
+
+        Self::clamp(part * layer_size, layer_size, view_size)
+    }
+
+    fn clamp(offset: f32, layer_size: f32, view_size: f32) -> f32 {
+        offset.clamp(0.0, (layer_size - view_size).max(0.0))
+    }
+}
@@ -0,0 +1,164 @@
+use crate::graph::{Graph, Point, PointCoord};
+
+// Change of one cell of the map
+struct Edit {
+    point_coord: PointCoord,
+    from: Point,
+    to: Point,
+}
+
+// Paints obstacles, erases them and drags starts and ends around the map.
+// Edits made while a mouse button is held make one stroke, undone and redone as a whole.
+pub struct Editor {
+    pub enabled: bool,
+    // Difficulty of the painted obstacles
+    pub difficulty: u32,
+    // Dragged start or end with the point it covers, restored once it moves on
+    dragged: Option<(PointCoord, Point)>,
+    // Edits are made only while a stroke lasts
+    stroke: Option<Vec<Edit>>,
+    strokes: Vec<Vec<Edit>>,
+    undone: Vec<Vec<Edit>>,
+}
+
+impl Editor {
+    pub const SAVE_PATH: &'static str = "map.txt";
+
+    pub fn new() -> Self {
+        Editor {
+            enabled: false,
+            difficulty: 3,
+            dragged: None,
+            stroke: None,
+            strokes: vec![],
+            undone: vec![],
+        }
+    }
+
+    // Edits of another map can't be undone
+    pub fn clear(&mut self) {
+        self.dragged = None;
+        self.stroke = None;
+        self.strokes = vec![];
+        self.undone = vec![];
+    }
+
+    pub fn next_difficulty(&mut self) {
+        self.difficulty = (self.difficulty + 1) % 4;
+    }
+
+    pub fn begin_stroke(&mut self) {
+        self.stroke = Some(vec![]);
+    }
+
+    pub fn end_stroke(&mut self) {
+        self.dragged = None;
+
+        if let Some(stroke) = self.stroke.take().filter(|stroke| !stroke.is_empty()) {
+            self.strokes.push(stroke);
+            // A new edit drops the undone ones
+            self.undone = vec![];
+        }
+    }
+
+    // A start or an end at `point_coord` is dragged by the stroke
+    pub fn grab(&mut self, graph: &Graph, point_coord: &PointCoord) {
+        if matches!(graph.point_at(point_coord), Point::Start | Point::End) {
+            self.dragged = Some((point_coord.clone(), Point::Free));
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragged.is_some()
+    }
+
+    // Only free cells, obstacles and terrain are painted over, the rest are kept
+    fn is_paintable(point: Point) -> bool {
+        matches!(
+            point,
+            Point::Free | Point::Obstacle { .. } | Point::Terrain { .. }
+        )
+    }
+
+    // Paints the cell with an obstacle of the chosen difficulty, `false` if it is kept
+    pub fn paint(&mut self, graph: &mut Graph, point_coord: &PointCoord) -> bool {
+        let obstacle = Point::Obstacle {
+            length: self.difficulty,
+        };
+
+        Self::is_paintable(graph.point_at(point_coord)) && self.set(graph, point_coord, obstacle)
+    }
+
+    // Clears an obstacle or terrain, `false` if there is none
+    pub fn erase(&mut self, graph: &mut Graph, point_coord: &PointCoord) -> bool {
+        Self::is_paintable(graph.point_at(point_coord)) && self.set(graph, point_coord, Point::Free)
+    }
+
+    // Moves the dragged start or end onto the cell, `false` if it stays
+    pub fn drag_to(&mut self, graph: &mut Graph, point_coord: &PointCoord) -> bool {
+        let (from, covered) = match self.dragged.clone() {
+            Some(dragged) => dragged,
+            None => return false,
+        };
+
+        let to = graph.point_at(point_coord);
+        if &from == point_coord || !Self::is_paintable(to) {
+            return false;
+        }
+
+        let dragged = graph.point_at(&from);
+        self.set(graph, &from, covered);
+        self.set(graph, point_coord, dragged);
+        self.dragged = Some((point_coord.clone(), to));
+
+        true
+    }
+
+    fn set(&mut self, graph: &mut Graph, point_coord: &PointCoord, point: Point) -> bool {
+        let from = graph.point_at(point_coord);
+
+        match &mut self.stroke {
+            Some(stroke) if from != point => {
+                graph.set_point(point_coord, point);
+                stroke.push(Edit {
+                    point_coord: point_coord.clone(),
+                    from,
+                    to: point,
+                });
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Reverts the last stroke, `false` if there is none
+    pub fn undo(&mut self, graph: &mut Graph) -> bool {
+        let stroke = match self.strokes.pop() {
+            Some(stroke) => stroke,
+            None => return false,
+        };
+
+        for edit in stroke.iter().rev() {
+            graph.set_point(&edit.point_coord, edit.from);
+        }
+        self.undone.push(stroke);
+
+        true
+    }
+
+    // Applies the last undone stroke again, `false` if there is none
+    pub fn redo(&mut self, graph: &mut Graph) -> bool {
+        let stroke = match self.undone.pop() {
+            Some(stroke) => stroke,
+            None => return false,
+        };
+
+        for edit in &stroke {
+            graph.set_point(&edit.point_coord, edit.to);
+        }
+        self.strokes.push(stroke);
+
+        true
+    }
+}
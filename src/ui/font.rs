@@ -0,0 +1,128 @@
+// 3x5 bitmap glyphs, every row is 3 bits from left to right
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+
+#[rustfmt::skip]
+const DIGITS: [[u8; GLYPH_HEIGHT as usize]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+#[rustfmt::skip]
+const LETTERS: [[u8; GLYPH_HEIGHT as usize]; 26] = [
+    [0b010, 0b101, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b110, 0b101, 0b110],
+    [0b011, 0b100, 0b100, 0b100, 0b011],
+    [0b110, 0b101, 0b101, 0b101, 0b110],
+    [0b111, 0b100, 0b110, 0b100, 0b111],
+    [0b111, 0b100, 0b110, 0b100, 0b100],
+    [0b011, 0b100, 0b101, 0b101, 0b011],
+    [0b101, 0b101, 0b111, 0b101, 0b101],
+    [0b111, 0b010, 0b010, 0b010, 0b111],
+    [0b001, 0b001, 0b001, 0b101, 0b010],
+    [0b101, 0b101, 0b110, 0b101, 0b101],
+    [0b100, 0b100, 0b100, 0b100, 0b111],
+    [0b101, 0b111, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b101, 0b101, 0b101],
+    [0b010, 0b101, 0b101, 0b101, 0b010],
+    [0b110, 0b101, 0b110, 0b100, 0b100],
+    [0b010, 0b101, 0b101, 0b110, 0b011],
+    [0b110, 0b101, 0b110, 0b101, 0b101],
+    [0b011, 0b100, 0b010, 0b001, 0b110],
+    [0b111, 0b010, 0b010, 0b010, 0b010],
+    [0b101, 0b101, 0b101, 0b101, 0b111],
+    [0b101, 0b101, 0b101, 0b101, 0b010],
+    [0b101, 0b101, 0b111, 0b111, 0b101],
+    [0b101, 0b101, 0b010, 0b101, 0b101],
+    [0b101, 0b101, 0b010, 0b010, 0b010],
+    [0b111, 0b001, 0b010, 0b100, 0b111],
+];
+
+#[rustfmt::skip]
+const PUNCTUATION: [(char, [u8; GLYPH_HEIGHT as usize]); 14] = [
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('\\', [0b100, 0b100, 0b010, 0b001, 0b001]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('*', [0b000, 0b101, 0b010, 0b101, 0b000]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+    ('(', [0b010, 0b100, 0b100, 0b100, 0b010]),
+    (')', [0b010, 0b001, 0b001, 0b001, 0b010]),
+    ('[', [0b110, 0b100, 0b100, 0b100, 0b110]),
+    (']', [0b011, 0b001, 0b001, 0b001, 0b011]),
+];
+
+// Letters are drawn uppercase, unknown characters (e.g. space) are left blank
+fn glyph(c: char) -> Option<&'static [u8; GLYPH_HEIGHT as usize]> {
+    if let Some(digit) = c.to_digit(10) {
+        return Some(&DIGITS[digit as usize]);
+    }
+    if c.is_ascii_alphabetic() {
+        return Some(&LETTERS[(c.to_ascii_uppercase() as u8 - b'A') as usize]);
+    }
+
+    PUNCTUATION
+        .iter()
+        .find(|(punctuation, _)| *punctuation == c)
+        .map(|(_, glyph)| glyph)
+}
+
+// Width and height of the drawn `text`
+pub fn text_size(scale: u32, text: &str) -> (u32, u32) {
+    let glyphs = text.chars().count() as u32;
+
+    (
+        (glyphs * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale,
+        GLYPH_HEIGHT * scale,
+    )
+}
+
+// Draws `text` with its top left corner at `x`, `y`, every glyph pixel is `scale` pixels wide
+pub fn draw_text(
+    frame: &mut [u8],
+    frame_width: u32,
+    (x, y): (u32, u32),
+    scale: u32,
+    text: &str,
+    color: &[u8],
+) {
+    let frame_height = frame.len() as u32 / 4 / frame_width;
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(glyph) = glyph(c) else {
+            continue;
+        };
+        let glyph_x = x + i as u32 * (GLYPH_WIDTH + 1) * scale;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+
+                for pixel_y in y + row as u32 * scale..y + (row as u32 + 1) * scale {
+                    for pixel_x in glyph_x + column * scale..glyph_x + (column + 1) * scale {
+                        if pixel_x >= frame_width || pixel_y >= frame_height {
+                            continue;
+                        }
+
+                        let i = ((pixel_y * frame_width + pixel_x) * 4) as usize;
+                        frame[i..i + 4].copy_from_slice(color);
+                    }
+                }
+            }
+        }
+    }
+}
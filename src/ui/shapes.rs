@@ -0,0 +1,139 @@
+fn put_pixel(frame: &mut [u8], frame_width: u32, (x, y): (i32, i32), color: &[u8]) {
+    let frame_height = frame.len() as u32 / 4 / frame_width;
+
+    if x < 0 || y < 0 || x as u32 >= frame_width || y as u32 >= frame_height {
+        return;
+    }
+
+    let i = ((y as u32 * frame_width + x as u32) * 4) as usize;
+    frame[i..i + 4].copy_from_slice(color);
+}
+
+// Bresenham's line from `from` to `to` inclusive
+fn line_points(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let mut points = vec![];
+
+    let (mut x, mut y) = from;
+    let (dx, dy) = ((to.0 - x).abs(), -(to.1 - y).abs());
+    let (sx, sy) = ((to.0 - x).signum(), (to.1 - y).signum());
+    let mut error = dx + dy;
+
+    loop {
+        points.push((x, y));
+
+        if (x, y) == to {
+            break;
+        }
+
+        let error_2 = 2 * error;
+        if error_2 >= dy {
+            error += dy;
+            x += sx;
+        }
+        if error_2 <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+
+    points
+}
+
+pub fn draw_line(
+    frame: &mut [u8],
+    frame_width: u32,
+    from: (i32, i32),
+    to: (i32, i32),
+    color: &[u8],
+) {
+    for point in line_points(from, to) {
+        put_pixel(frame, frame_width, point, color);
+    }
+}
+
+// Line `2 * radius + 1` pixels wide with rounded ends
+pub fn draw_thick_line(
+    frame: &mut [u8],
+    frame_width: u32,
+    from: (i32, i32),
+    to: (i32, i32),
+    radius: i32,
+    color: &[u8],
+) {
+    for point in line_points(from, to) {
+        draw_disc(frame, frame_width, point, radius, color);
+    }
+}
+
+// Arrow from `from` to `to` with the head at `to`
+pub fn draw_arrow(
+    frame: &mut [u8],
+    frame_width: u32,
+    from: (i32, i32),
+    to: (i32, i32),
+    radius: i32,
+    color: &[u8],
+) {
+    draw_thick_line(frame, frame_width, from, to, radius, color);
+
+    let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+    // Head sides go back from the tip at 30 degrees, half as long as the shaft
+    let (along, across) = (0.866_025_4 * 0.5, 0.5 * 0.5);
+
+    for across in [across, -across] {
+        let side = (
+            to.0 - (dx * along - dy * across) as i32,
+            to.1 - (dx * across + dy * along) as i32,
+        );
+
+        draw_thick_line(frame, frame_width, to, side, radius, color);
+    }
+}
+
+pub fn fill_rect(
+    frame: &mut [u8],
+    frame_width: u32,
+    (x, y): (i32, i32),
+    (width, height): (i32, i32),
+    color: &[u8],
+) {
+    for pixel_y in y..y + height {
+        for pixel_x in x..x + width {
+            put_pixel(frame, frame_width, (pixel_x, pixel_y), color);
+        }
+    }
+}
+
+// One pixel wide edges of the rectangle
+pub fn draw_rect(
+    frame: &mut [u8],
+    frame_width: u32,
+    (x, y): (i32, i32),
+    (width, height): (i32, i32),
+    color: &[u8],
+) {
+    for (from, to) in [
+        ((x, y), (x + width - 1, y)),
+        ((x, y + height - 1), (x + width - 1, y + height - 1)),
+        ((x, y), (x, y + height - 1)),
+        ((x + width - 1, y), (x + width - 1, y + height - 1)),
+    ] {
+        draw_line(frame, frame_width, from, to, color);
+    }
+}
+
+pub fn draw_disc(
+    frame: &mut [u8],
+    frame_width: u32,
+    (center_x, center_y): (i32, i32),
+    radius: i32,
+    color: &[u8],
+) {
+    for y in -radius..=radius {
+        for x in -radius..=radius {
+            if x * x + y * y <= radius * radius {
+                put_pixel(frame, frame_width, (center_x + x, center_y + y), color);
+            }
+        }
+    }
+}